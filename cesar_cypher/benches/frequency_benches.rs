@@ -0,0 +1,64 @@
+use std::time::Duration;
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use cesar_cypher::{Analizer, LanguageModel};
+
+const SAMPLE: &str = "Wkh txlfn eurzq ira mxpsv ryhu wkh odcb grj, zkloh \
+	wkh qljkw zdwfk uhsruwv qr vljq ri wkh hqhpb. ";
+
+macro_rules! meassure {
+	(name = $name:expr, amount_bytes = $a:expr, bencher = $b:ident) => {
+		let analizer = Analizer::new(LanguageModel::default());
+		let text = SAMPLE.repeat($a / SAMPLE.len());
+
+		let mut group = $b.benchmark_group($name);
+		group.bench_function(
+			"Collected characters",
+			|b| b.iter(|| analizer.calculate_frequency(
+					black_box(&text.chars().collect::<Vec<_>>())
+				)
+			)
+		);
+
+		group.bench_function(
+			"Streamed",
+			|b| b.iter(|| analizer.stream_frequency(
+					black_box(text.as_bytes()),
+					false
+				)
+			)
+		);
+
+		group.bench_function(
+			"Streamed in parallel",
+			|b| b.iter(|| analizer.stream_frequency(
+					black_box(text.as_bytes()),
+					true
+				)
+			)
+		);
+
+		group.finish();
+	};
+}
+
+fn count_100_kb(c: &mut Criterion) {
+	meassure!(name = "Frequency of 100 KB", amount_bytes = 100_000, bencher = c);
+}
+
+fn count_1_mb(c: &mut Criterion) {
+	meassure!(name = "Frequency of 1 MB", amount_bytes = 1_000_000, bencher = c);
+}
+
+fn count_10_mb(c: &mut Criterion) {
+	meassure!(name = "Frequency of 10 MB", amount_bytes = 10_000_000, bencher = c);
+}
+
+criterion_group!(
+	name = benches;
+	config = Criterion::default()
+		.measurement_time(Duration::from_millis(500))
+		.sample_size(10);
+	targets = count_100_kb, count_1_mb, count_10_mb
+);
+
+criterion_main!(benches);
@@ -0,0 +1,291 @@
+use std::{path::PathBuf, str::FromStr};
+
+use structopt::{clap::AppSettings, StructOpt};
+
+use crate::{cipher::CipherKind, data::{model::Language, Variant}, output::OutputFormat};
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+	name = "Cesar Cypher",
+	author = "Bryan Baron <MrFixThis>",
+	rename_all = "kebab-case",
+	setting = AppSettings::SubcommandsNegateReqs
+)]
+pub struct CliArgs {
+	/// Cesar Cypher's Sub-commands
+	#[structopt(subcommand)]
+	pub cmds: Option<SubCmds>,
+
+	/// Analysis to perform over the cyphered text.
+	///
+	/// The `interactive` mode reads its commands from the standard input and
+	/// writes its prompts to the standard error, so the text has to be given
+	/// as arguments or through an `--input` file other than `-`.
+	#[structopt(
+		short,
+		long,
+		default_value = "shift",
+		possible_values = Mode::VARIANTS,
+		case_insensitive = true
+	)]
+	pub mode: Mode,
+
+	/// Bundled language model to compare the cyphered text against.
+	///
+	/// Only the English model bundles quadgrams; the modes that score their
+	/// candidates with n-grams fall back to bigrams for the other languages,
+	/// which is seldom enough to crack a transposition or a substitution.
+	#[structopt(
+		short,
+		long,
+		default_value = "english",
+		possible_values = Language::VARIANTS,
+		case_insensitive = true
+	)]
+	pub language: Language,
+
+	/// Language model file to use instead of the bundled ones.
+	#[structopt(long, parse(from_os_str))]
+	pub model: Option<PathBuf>,
+
+	/// Detect the language of the text among the bundled models, and the one
+	/// given with --model, and use the most likely one instead of --language.
+	#[structopt(short, long)]
+	pub detect: bool,
+
+	/// Keep the accented letters that are not part of the language's alphabet
+	/// out of the analysis, instead of counting them as their base letter.
+	#[structopt(long)]
+	pub keep_accents: bool,
+
+	/// Dispatch the text straight to the solver of the cypher identified by the
+	/// `identify` mode.
+	#[structopt(long)]
+	pub solve: bool,
+
+	/// Count the frequency of the letters of the text in parallel, which pays
+	/// off for large inputs.
+	#[structopt(long)]
+	pub parallel: bool,
+
+	/// Known word of the plain text, such as a name or a greeting.
+	///
+	/// The `shift`, `frequency` and `substitution` modes slide it across the
+	/// text and report every position it fits in without conflicting with
+	/// their key, along with the decryption of the key it implies. The last
+	/// two complete the letters the crib leaves unknown at every position,
+	/// after their frequency rank in the `frequency` mode, and by annealing
+	/// with at most two restarts in the `substitution` one.
+	#[structopt(long)]
+	pub crib: Option<String>,
+
+	/// Infer the keyword and shift the key recovered by the `substitution` and
+	/// `pattern` modes was generated from, and fill the letters it leaves
+	/// unknown after them.
+	#[structopt(long)]
+	pub keyword: bool,
+
+	/// Number of candidates to show for the modes that rank their results, or
+	/// of repeated sequences for the `stats` mode.
+	#[structopt(short, long, default_value = "5")]
+	pub top: usize,
+
+	/// Number of random restarts of the key search for the substitution and
+	/// homophonic modes, and of the search of every key length for the
+	/// columnar mode.
+	///
+	/// Defaults to 20, but to 5 for the homophonic mode, since each one of its
+	/// restarts anneals thousands of moves for every symbol of the text, which
+	/// takes about half a second over a few hundred symbols.
+	#[structopt(short, long)]
+	pub restarts: Option<usize>,
+
+	/// Variant of the polyalphabetic cypher for the polyalphabetic mode.
+	#[structopt(
+		long,
+		default_value = "vigenere",
+		possible_values = Variant::VARIANTS,
+		case_insensitive = true
+	)]
+	pub variant: Variant,
+
+	/// Longest key length considered by the polyalphabetic and columnar modes,
+	/// and largest amount of rails tried by the rail-fence mode.
+	#[structopt(long, default_value = "20")]
+	pub max_key_len: usize,
+
+	/// Wordlist file, with a word per line, for the pattern mode. The words of
+	/// the language model are used when it is not given.
+	#[structopt(short, long, parse(from_os_str))]
+	pub wordlist: Option<PathBuf>,
+
+	/// File to read the text from, or `-` for the standard input.
+	///
+	/// When neither this option nor a text is given, the text is read from the
+	/// standard input as long as it is piped into the program. The `frequency`
+	/// mode counts the text as it reads it, without a crib or `--detect`, and
+	/// only decrypts its first characters.
+	#[structopt(short, long, global = true, parse(from_os_str))]
+	pub input: Option<PathBuf>,
+
+	/// File to write the report to, instead of the standard output.
+	#[structopt(short, long, global = true, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Format of the report. `json` and `csv` follow a stable schema meant to
+	/// be consumed by other programs.
+	#[structopt(
+		short,
+		long,
+		global = true,
+		default_value = "table",
+		possible_values = OutputFormat::VARIANTS,
+		case_insensitive = true
+	)]
+	pub format: OutputFormat,
+
+	/// The cyphered text to analyze. Several values are joined with spaces.
+	pub text: Vec<String>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum SubCmds {
+	/// Cypher a plain text with a known key.
+	Encrypt {
+		#[structopt(flatten)]
+		cipher: CipherArgs,
+
+		/// The plain text to cypher. Several values are joined with spaces.
+		text: Vec<String>,
+	},
+
+	/// Decipher a cyphered text with a known key.
+	Decrypt {
+		#[structopt(flatten)]
+		cipher: CipherArgs,
+
+		/// The cyphered text to decipher. Several values are joined with spaces.
+		text: Vec<String>,
+	},
+
+	/// Build a language model out of the letters, n-grams and words of a
+	/// corpus. The model can be loaded afterwards with the --model option.
+	Train(TrainArgs),
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct CipherArgs {
+	/// Cypher used to transform the text.
+	#[structopt(
+		short,
+		long,
+		required = true,
+		possible_values = CipherKind::VARIANTS,
+		case_insensitive = true
+	)]
+	pub cipher: CipherKind,
+
+	/// Key of the cypher.
+	///
+	/// It is specified as follows: a number or a letter for `caesar`, a word for
+	/// `keyword` and `vigenere`, `a/b` for `affine`, the plain alphabet for
+	/// `substitution` and nothing for `atbash`.
+	#[structopt(short, long)]
+	pub key: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct TrainArgs {
+	/// Name of the language of the model.
+	#[structopt(short, long)]
+	pub name: Option<String>,
+
+	/// Drop the diacritics of the letters of the corpus.
+	#[structopt(long)]
+	pub strip_accents: bool,
+
+	/// Number of the most frequent quadgrams kept in the model.
+	#[structopt(long, default_value = "10000")]
+	pub max_quadgrams: usize,
+
+	/// Number of the most frequent words kept in the model.
+	#[structopt(long, default_value = "100")]
+	pub max_words: usize,
+
+	/// The files the corpus is made of.
+	#[structopt(required = true, parse(from_os_str))]
+	pub corpus: Vec<PathBuf>,
+}
+
+/// The available analysis modes of the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+	/// Brute-forces every rotation of a Caesar cypher.
+	Shift,
+	/// Associates the text's frequency with the language's one by rank.
+	Frequency,
+	/// Searches the whole key of a monoalphabetic substitution cypher.
+	Substitution,
+	/// Searches the key of a homophonic cypher, whose letters may be written
+	/// with several symbols.
+	Homophonic,
+	/// Recovers the keyword of a Vigenère-like polyalphabetic cypher.
+	Polyalphabetic,
+	/// Brute-forces every valid key of an affine cypher.
+	Affine,
+	/// Brute-forces every amount of rails and offset of a rail-fence cypher.
+	RailFence,
+	/// Searches the order of the columns of a columnar transposition.
+	Columnar,
+	/// Cracks the text as both a rail-fence and a columnar transposition.
+	Transposition,
+	/// Matches the words of the text against a dictionary by letter pattern.
+	Pattern,
+	/// Refines the key found by the frequency analysis by hand.
+	Interactive,
+	/// Prints the statistics of the text, to tell which cypher produced it.
+	Stats,
+	/// Identifies the type of cypher that produced the text.
+	Identify,
+}
+
+impl Mode {
+	pub const VARIANTS: &'static [&'static str] = &[
+		"shift", "frequency", "substitution", "homophonic", "polyalphabetic", "affine", "rail-fence", "columnar",
+		"transposition", "pattern", "interactive", "stats", "identify"
+	];
+
+	/// Retrieves the amount of restarts of the key search used by the mode
+	/// when none is given.
+	pub fn default_restarts(self) -> usize {
+		match self {
+			Self::Homophonic => 5,
+			_ => 20
+		}
+	}
+}
+
+impl FromStr for Mode {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"shift" => Ok(Self::Shift),
+			"frequency" => Ok(Self::Frequency),
+			"substitution" => Ok(Self::Substitution),
+			"homophonic" => Ok(Self::Homophonic),
+			"polyalphabetic" => Ok(Self::Polyalphabetic),
+			"affine" => Ok(Self::Affine),
+			"rail-fence" => Ok(Self::RailFence),
+			"columnar" => Ok(Self::Columnar),
+			"transposition" => Ok(Self::Transposition),
+			"pattern" => Ok(Self::Pattern),
+			"interactive" => Ok(Self::Interactive),
+			"stats" => Ok(Self::Stats),
+			"identify" => Ok(Self::Identify),
+			unk => Err(format!(r#"unknown mode "{unk}""#))
+		}
+	}
+}
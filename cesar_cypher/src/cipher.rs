@@ -0,0 +1,354 @@
+use std::str::FromStr;
+
+use crate::{data::{SubstitutionKey, ALPHABET_LEN}, error::Error};
+
+/// A classical cypher.
+///
+/// Abstract and concrete transformation of a text with a known key. Every
+/// implementation only transforms the letters of the text, keeping their case,
+/// and leaves the rest of the characters untouched.
+pub trait Cipher {
+	/// Cyphers the given plain text.
+	fn encrypt(&self, text: &str) -> String;
+
+	/// Deciphers the given cyphered text.
+	fn decrypt(&self, text: &str) -> String;
+}
+
+/// The Caesar cypher, which shifts every letter by a fixed amount.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caesar {
+	shift: u8
+}
+
+impl Caesar {
+	pub fn new(shift: u8) -> Self {
+		Self { shift: shift % ALPHABET_LEN as u8 }
+	}
+}
+
+impl Cipher for Caesar {
+	fn encrypt(&self, text: &str) -> String {
+		map_letters(text, |c| (c + self.shift) % ALPHABET_LEN as u8)
+	}
+
+	fn decrypt(&self, text: &str) -> String {
+		map_letters(text, |c| (c + ALPHABET_LEN as u8 - self.shift) % ALPHABET_LEN as u8)
+	}
+}
+
+/// The Atbash cypher, which reverses the alphabet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Atbash;
+
+impl Cipher for Atbash {
+	fn encrypt(&self, text: &str) -> String {
+		map_letters(text, |c| ALPHABET_LEN as u8 - 1 - c)
+	}
+
+	fn decrypt(&self, text: &str) -> String {
+		self.encrypt(text)
+	}
+}
+
+/// The affine cypher, which maps every letter `x` to `a * x + b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Affine {
+	a: u8,
+	b: u8,
+	a_inv: u8
+}
+
+impl Affine {
+	/// Creates a new affine cypher, ensuring that `a` is coprime with the
+	/// length of the alphabet, so the cypher can be reverted.
+	pub fn new(a: u8, b: u8) -> Result<Self, Error> {
+		let a = a % ALPHABET_LEN as u8;
+		let a_inv = (1..ALPHABET_LEN as u8)
+			.find(|i| (a as u32 * *i as u32) % ALPHABET_LEN as u32 == 1)
+			.ok_or_else(|| Error::InvalidKey(format!("{a} is not coprime with {ALPHABET_LEN}")))?;
+
+		Ok(Self { a, b: b % ALPHABET_LEN as u8, a_inv })
+	}
+}
+
+impl Cipher for Affine {
+	fn encrypt(&self, text: &str) -> String {
+		map_letters(text, |c| ((self.a as u32 * c as u32 + self.b as u32) % ALPHABET_LEN as u32) as u8)
+	}
+
+	fn decrypt(&self, text: &str) -> String {
+		map_letters(text, |c| {
+			let shifted = (c + ALPHABET_LEN as u8 - self.b) as u32;
+			((self.a_inv as u32 * shifted) % ALPHABET_LEN as u32) as u8
+		})
+	}
+}
+
+/// The monoalphabetic substitution cypher, given by the complete key that
+/// deciphers it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+	key: SubstitutionKey,
+	inverse: SubstitutionKey
+}
+
+impl Substitution {
+	pub fn new(key: SubstitutionKey) -> Result<Self, Error> {
+		if !key.is_complete() {
+			return Err(Error::InvalidKey(format!("the key {key} does not map every letter")));
+		}
+
+		Ok(Self { inverse: key.inverse(), key })
+	}
+}
+
+impl Cipher for Substitution {
+	fn encrypt(&self, text: &str) -> String {
+		self.inverse.apply(text)
+	}
+
+	fn decrypt(&self, text: &str) -> String {
+		self.key.apply(text)
+	}
+}
+
+/// The keyword substitution cypher, whose cyphered alphabet is made of the
+/// letters of a keyword followed by the rest of the alphabet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keyword {
+	substitution: Substitution
+}
+
+impl Keyword {
+	pub fn new(keyword: &str) -> Result<Self, Error> {
+		let letters = parse_keyword(keyword)?;
+
+		let mut alphabet: Vec<u8> = Vec::with_capacity(ALPHABET_LEN);
+		for c in letters.into_iter().chain(0..ALPHABET_LEN as u8) {
+			if !alphabet.contains(&c) { alphabet.push(c); }
+		}
+
+		// the cyphered alphabet is the key that cyphers the text, so the one
+		// that deciphers it is its inverse
+		let mut table = [0; ALPHABET_LEN];
+		table.copy_from_slice(&alphabet);
+		let key = SubstitutionKey::from_table(table).expect("keyword alphabets are bijective");
+
+		Ok(Self { substitution: Substitution::new(key.inverse())? })
+	}
+}
+
+impl Cipher for Keyword {
+	fn encrypt(&self, text: &str) -> String {
+		self.substitution.encrypt(text)
+	}
+
+	fn decrypt(&self, text: &str) -> String {
+		self.substitution.decrypt(text)
+	}
+}
+
+/// The Vigenère cypher, which shifts every letter by the next letter of a
+/// repeating keyword.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vigenere {
+	key: Vec<u8>
+}
+
+impl Vigenere {
+	pub fn new(keyword: &str) -> Result<Self, Error> {
+		Ok(Self { key: parse_keyword(keyword)? })
+	}
+
+	/// Shifts the letters of the text by the keyword, forward or backward.
+	fn apply(&self, text: &str, forward: bool) -> String {
+		let mut key_iter = self.key.iter().cycle();
+		map_letters(text, |c| {
+			let k = *key_iter.next().unwrap_or(&0);
+			match forward {
+				true => (c + k) % ALPHABET_LEN as u8,
+				false => (c + ALPHABET_LEN as u8 - k) % ALPHABET_LEN as u8
+			}
+		})
+	}
+}
+
+impl Cipher for Vigenere {
+	fn encrypt(&self, text: &str) -> String {
+		self.apply(text, true)
+	}
+
+	fn decrypt(&self, text: &str) -> String {
+		self.apply(text, false)
+	}
+}
+
+/// The cyphers that can be built from the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherKind {
+	Caesar,
+	Keyword,
+	Atbash,
+	Affine,
+	Vigenere,
+	Substitution
+}
+
+impl CipherKind {
+	pub const VARIANTS: &'static [&'static str] = &["caesar", "keyword", "atbash", "affine", "vigenere", "substitution"];
+
+	/// Builds the cypher with the given key.
+	///
+	/// The key is expected as follows, depending on the cypher:
+	///
+	/// - Caesar: the shift, either as a number or as a letter.
+	/// - Keyword and Vigenère: a word.
+	/// - Atbash: no key at all.
+	/// - Affine: `a/b`.
+	/// - Substitution: the plain alphabet, as written by the substitution
+	///   mode.
+	pub fn build(&self, key: Option<&str>) -> Result<Box<dyn Cipher>, Error> {
+		let key = match (self, key) {
+			(Self::Atbash, _) => return Ok(Box::new(Atbash)),
+			(_, Some(key)) => key.trim(),
+			(_, None) => return Err(Error::InvalidKey(
+				"missing key. specify one with the --key option".to_owned()
+			))
+		};
+
+		Ok(match self {
+			Self::Caesar => Box::new(Caesar::new(parse_shift(key)?)),
+			Self::Keyword => Box::new(Keyword::new(key)?),
+			Self::Affine => {
+				let (a, b) = key
+					.split_once('/')
+					.and_then(|(a, b)| Some((a.trim().parse().ok()?, b.trim().parse().ok()?)))
+					.ok_or_else(|| Error::InvalidKey(format!(r#""{key}" is not an affine key. expected a/b"#)))?;
+				Box::new(Affine::new(a, b)?)
+			},
+			Self::Vigenere => Box::new(Vigenere::new(key)?),
+			Self::Substitution => Box::new(Substitution::new(key.parse()?)?),
+			Self::Atbash => unreachable!()
+		})
+	}
+}
+
+impl FromStr for CipherKind {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"caesar" => Ok(Self::Caesar),
+			"keyword" => Ok(Self::Keyword),
+			"atbash" => Ok(Self::Atbash),
+			"affine" => Ok(Self::Affine),
+			"vigenere" => Ok(Self::Vigenere),
+			"substitution" => Ok(Self::Substitution),
+			unk => Err(format!(r#"unknown cipher "{unk}""#))
+		}
+	}
+}
+
+/// Transforms every letter of the text, expressed as its position inside the
+/// alphabet, keeping its case and leaving the rest of the characters untouched.
+fn map_letters<F>(text: &str, mut f: F) -> String
+	where F: FnMut(u8) -> u8
+{
+	text
+		.chars()
+		.map(|c| match c {
+			'A'..='Z' => (f(c as u8 - b'A') + b'A') as char,
+			'a'..='z' => (f(c as u8 - b'a') + b'a') as char,
+			_ => c
+		})
+		.collect()
+}
+
+/// Parses a Caesar shift given either as a number or as a letter.
+fn parse_shift(key: &str) -> Result<u8, Error> {
+	if let Ok(n) = key.parse::<u32>() {
+		return Ok((n % ALPHABET_LEN as u32) as u8);
+	}
+
+	match parse_keyword(key)?.as_slice() {
+		[k] => Ok(*k),
+		_ => Err(Error::InvalidKey(format!(r#""{key}" is not a shift. expected a number or a single letter"#)))
+	}
+}
+
+/// Parses a keyword into the positions of its letters inside the alphabet.
+fn parse_keyword(keyword: &str) -> Result<Vec<u8>, Error> {
+	if keyword.is_empty() {
+		return Err(Error::InvalidKey("the keyword is empty".to_owned()));
+	}
+	if let Some(c) = keyword.chars().find(|c| !c.is_ascii_alphabetic()) {
+		return Err(Error::UnsupportedSymbol(c));
+	}
+
+	Ok(keyword.bytes().map(|c| c.to_ascii_uppercase() - b'A').collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PLAIN: &str = "Attack at Dawn! Meet me by the old bridge, at 5 o'clock.";
+
+	fn assert_round_trip(cipher: &dyn Cipher) {
+		let cyphered = cipher.encrypt(PLAIN);
+
+		assert_ne!(cyphered, PLAIN);
+		assert_eq!(cipher.decrypt(&cyphered), PLAIN);
+	}
+
+	#[test]
+	fn caesar_round_trips() {
+		assert_eq!(Caesar::new(3).encrypt("Xyz, abc"), "Abc, def");
+		assert_round_trip(&Caesar::new(3));
+	}
+
+	#[test]
+	fn atbash_round_trips() {
+		assert_eq!(Atbash.encrypt("Abc"), "Zyx");
+		assert_round_trip(&Atbash);
+	}
+
+	#[test]
+	fn affine_round_trips() {
+		assert_eq!(Affine::new(5, 8).unwrap().encrypt("AFFINE"), "IHHWVC");
+		assert_round_trip(&Affine::new(5, 8).unwrap());
+		assert!(Affine::new(13, 2).is_err());
+	}
+
+	#[test]
+	fn keyword_round_trips() {
+		assert_eq!(Keyword::new("zebras").unwrap().encrypt("ABCDEFG"), "ZEBRASC");
+		assert_round_trip(&Keyword::new("zebras").unwrap());
+		assert!(matches!(Keyword::new("zeb ras"), Err(Error::UnsupportedSymbol(' '))));
+	}
+
+	#[test]
+	fn substitution_round_trips() {
+		let cipher = CipherKind::Substitution.build(Some("QWERTYUIOPASDFGHJKLZXCVBNM")).unwrap();
+
+		assert_eq!(cipher.decrypt("Abc"), "Qwe");
+		assert_round_trip(cipher.as_ref());
+		assert!(CipherKind::Substitution.build(Some("QWERTYUIOPASDFGHJKLZXCVBN-")).is_err());
+	}
+
+	#[test]
+	fn vigenere_round_trips() {
+		assert_eq!(Vigenere::new("LEMON").unwrap().encrypt("ATTACK AT DAWN"), "LXFOPV EF RNHR");
+		assert_round_trip(&Vigenere::new("LEMON").unwrap());
+	}
+
+	#[test]
+	fn ciphers_are_built() {
+		assert_eq!(CipherKind::Caesar.build(Some("D")).unwrap().encrypt("A"), "D");
+		assert_eq!(CipherKind::Affine.build(Some("5/8")).unwrap().encrypt("A"), "I");
+		assert!(CipherKind::Affine.build(Some("5,8")).is_err());
+		assert!(CipherKind::Vigenere.build(None).is_err());
+		assert!(CipherKind::Atbash.build(None).is_ok());
+	}
+}
@@ -1,19 +1,68 @@
+//! The analysis of the cyphered texts: the language models, the statistics
+//! of the texts and the solvers of every supported cypher.
+
+mod affine;
+mod alphabet;
+mod counter;
+mod cracker;
+mod crib;
+mod detect;
+mod fitness;
+mod homophonic;
+mod identify;
+pub mod key;
+mod keyword;
+pub mod model;
+mod pattern;
+mod rank;
+mod shift;
+mod stats;
+mod substitution;
+mod train;
+mod transposition;
+mod vigenere;
+
 use std::collections::HashMap;
 
-use aho_corasick::AhoCorasick;
-
-/// This array (in the future a Vector) contains the English's language
-/// frequency analysis that will be used to associate its values to the ones
-/// retrieved from the resulting frequency analysis over the encripted text
-/// passed to the program
-const LETTERS_FREQ: [(char, f64); 26] = [
-	('E', 11.160), ('A', 8.4966), ('R', 7.5809), ('I', 7.5448), ('O', 7.1635),
-	('T', 6.9509), ('N', 6.6544), ('S', 5.7351), ('L', 5.4893), ('C', 4.5388),
-	('U', 3.6308), ('D', 3.3844), ('P', 3.1671), ('M', 3.0129), ('H', 3.0034),
-	('G', 2.4705), ('B', 2.0720), ('F', 1.8121), ('Y', 1.7779), ('W', 1.2899),
-	('K', 1.1016), ('V', 1.0074), ('X', 0.2902), ('Z', 0.2722), ('J', 0.1965),
-	('Q', 0.1962)
-];
+use model::LanguageModel;
+
+use crate::error::Error;
+
+pub use alphabet::Alphabet;
+pub use cracker::{
+	AffineCracker, ColumnarCracker, Cracker, FrequencyCracker, HomophonicCracker, PolyalphabeticCracker, RailFenceCracker,
+	ShiftCracker, SubstitutionCracker
+};
+pub use crib::{CribKey, CribPlacement};
+pub use detect::{detect_language, LanguageGuess};
+pub use homophonic::HomophonicSolution;
+pub use identify::{CipherFeatures, CipherGuess, CipherType, Identification};
+pub(crate) use alphabet::{fold_case, match_case, strip_accent};
+pub use pattern::{PatternSolution, WordPatterns};
+pub use key::SubstitutionKey;
+pub use keyword::KeywordKey;
+pub use stats::{Repeat, TextStats};
+pub use substitution::SubstitutionSolution;
+pub use train::Trainer;
+pub use vigenere::{KeyLength, PolyalphabeticSolution, Variant};
+
+/// Amount of letters in the latin alphabet the solvers of the `Analizer` work
+/// with.
+pub const ALPHABET_LEN: usize = 26;
+
+/// This struct represents a possible solution found by any of the solvers of
+/// the `Analizer`, along with the key that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+	/// Human readable representation of the key that produced the candidate.
+	pub key: String,
+	/// The text resulting from applying the key over the cyphered text.
+	pub text: String,
+	/// The raw score given by the solver. The lower, the better.
+	pub score: f64,
+	/// The relative confidence (from 0 to 1) of the candidate among the rest.
+	pub confidence: f64
+}
 
 /// This structs represents an analizer process over some cyphered text
 /// passed to the program.
@@ -23,143 +72,460 @@ const LETTERS_FREQ: [(char, f64); 26] = [
 /// if you are actually working with data that fits the context of the dilema.
 #[derive(Debug)]
 pub struct Analizer {
-	_eng_freq: Vec<(char, f64)>
+	model: LanguageModel,
+	alphabet: Alphabet
 }
 
 impl Analizer {
-	/// Creates a new `Analizer` instance.
-	pub fn new() -> Self {
-		Self {
-			_eng_freq: Vec::from(LETTERS_FREQ)
-		}
+	/// Creates a new `Analizer` instance that compares the cyphered texts
+	/// against the given language model, over the alphabet made of the
+	/// letters of the model.
+	pub fn new(model: LanguageModel) -> Self {
+		let alphabet = Alphabet::from_model(&model);
+		Self::with_alphabet(model, alphabet)
 	}
 
-	/// Retrieves the vector containing the `English`'s frequency analysis values.
-	pub fn get_english_freqs(&self) -> &Vec<(char, f64)> {
-		&self._eng_freq
+	/// Creates a new `Analizer` instance that works over the given alphabet.
+	pub fn with_alphabet(model: LanguageModel, alphabet: Alphabet) -> Self {
+		Self { model, alphabet }
 	}
 
-	/// Determines the frequency of each character inside a given text.
-	pub fn calculate_frequency<'a>(
-		&self,
-		text_chars: &'a [char]
-	) -> Vec<(&'a char, f64)>
-	{
-		let mut freq: HashMap<&'a char, f64> = HashMap::new();
+	/// Retrieves the language model used by the analizer.
+	pub fn get_model(&self) -> &LanguageModel {
+		&self.model
+	}
+
+	/// Retrieves the alphabet the texts are analyzed over.
+	pub fn get_alphabet(&self) -> &Alphabet {
+		&self.alphabet
+	}
+
+	/// Retrieves the language's frequency analysis values.
+	pub fn get_language_freqs(&self) -> &[(char, f64)] {
+		self.model.letters()
+	}
+
+	/// Determines the frequency of each letter inside a given text.
+	///
+	/// Only the letters of the alphabet are taken into account, once
+	/// normalized, so spaces, digits and punctuation do not skew the analysis.
+	pub fn calculate_frequency(&self, text_chars: &[char]) -> Result<Vec<(char, f64)>, Error> {
+		let mut freq: HashMap<char, f64> = HashMap::new();
 
 		// here, we iterate over the given text to determine how many times
-		// each character is inside it
-		for c in text_chars.iter() { *freq.entry(c).or_insert(0.0) += 1.0; }
+		// each letter is inside it
+		let mut text_len = 0;
+		for c in text_chars.iter().filter_map(|&c| self.alphabet.normalize(c)) {
+			*freq.entry(c).or_insert(0.0) += 1.0;
+			text_len += 1;
+		}
+		if text_len == 0 {
+			return Err(Error::EmptyInput);
+		}
 
-		// then, we calculate the frequency of each character with the formula:
+		// then, we calculate the frequency of each letter with the formula:
 		// f = c / l
 		// where:
 		// f = frequency
-		// c = character
-		// l = length of the text
-		let text_len = text_chars.len();
+		// c = letter
+		// l = amount of letters in the text
 		for (_, f) in freq.iter_mut() {
-			*f = *f / text_len as f64;
+			*f /= text_len as f64;
 		}
 
-		// finally, the BTreeMap is converted to a vector sorted vector.
+		// finally, the HashMap is converted to a vector sorted by frequency.
 		let mut freq_vec: Vec<_> = freq.into_iter().collect();
-		freq_vec.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-		freq_vec.reverse();
+		freq_vec.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
 
-		freq_vec
+		Ok(freq_vec)
+	}
+
+	/// Determines the frequency of each n-gram of the given length inside a
+	/// given text.
+	///
+	/// The n-grams are taken over the letters of the alphabet only, once
+	/// normalized, so they may span several words, just like the solvers
+	/// score them.
+	pub fn calculate_ngram_frequency(&self, text_chars: &[char], n: usize) -> Result<Vec<(String, f64)>, Error> {
+		let letters: Vec<char> = text_chars.iter().filter_map(|&c| self.alphabet.normalize(c)).collect();
+		if letters.len() < n.max(1) {
+			return Err(Error::EmptyInput);
+		}
+
+		let mut counts: HashMap<String, usize> = HashMap::new();
+		let windows = letters.windows(n.max(1));
+		let total = windows.len() as f64;
+		for gram in windows {
+			*counts.entry(gram.iter().collect()).or_insert(0) += 1;
+		}
+
+		let mut freq_vec: Vec<_> = counts.into_iter().map(|(g, c)| (g, c as f64 / total)).collect();
+		freq_vec.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+		Ok(freq_vec)
 	}
 
 	/// Associates the a given text's frequency with the frequency given by the
-	/// `English`'s language frequency analysis.
-	pub fn associate_frequency<'a, 'b>(
-		&'a self,
+	/// language's frequency analysis.
+	///
+	/// The letters sharing a same frequency are associated with a same letter
+	/// of the language, while the ones that appear a single time are left
+	/// unknown (`?`), since there is not enough data to place them.
+	pub fn associate_frequency(
+		&self,
 		text: &str,
-		freq: &'b [(&'a char, f64)]
-	) -> Vec<(&'a char, &'b char)>
+		freq: &[(char, f64)]
+	) -> Result<Vec<(char, char)>, Error>
+	{
+		let text_len = text.chars().filter(|&c| self.alphabet.contains(c)).count();
+		self.associate_levels(text_len, freq)
+	}
+
+	/// Same as [`Analizer::associate_frequency`], but given the amount of
+	/// letters of the text instead of the text itself, which a streamed text
+	/// does not keep.
+	pub(crate) fn associate_levels(
+		&self,
+		text_len: usize,
+		freq: &[(char, f64)]
+	) -> Result<Vec<(char, char)>, Error>
 	{
-		let mut eng_freq_iter = self._eng_freq.iter();
-		let mut eng_freq_item = eng_freq_iter.next().unwrap();
-		let mut prev_val = &freq[0].1;
-		freq
-			.into_iter()
-			.map(|(c, f)| {
-				match *f {
-					n if n != 1.0 / text.len() as f64 => {
-						if prev_val != f {
-							eng_freq_item = eng_freq_iter.next().unwrap();
-						}
-						prev_val = f;
-						(*c, &eng_freq_item.0)
-					},
-					_ => (*c, &'?')
-				}
+		if text_len == 0 || freq.is_empty() {
+			return Err(Error::EmptyInput);
+		}
+
+		// every distinct frequency, but the one of the single letters, takes
+		// the next letter of the language
+		let single = 1.0 / text_len as f64;
+		let mut levels: Vec<f64> = freq.iter().map(|(_, f)| *f).filter(|&f| f != single).collect();
+		levels.dedup();
+
+		let letters = self.model.letters();
+		if levels.len() > letters.len() {
+			return Err(Error::AlphabetOverflow { levels: levels.len(), letters: letters.len() });
+		}
+
+		Ok(freq
+			.iter()
+			.map(|&(c, f)| match levels.iter().position(|&l| l == f) {
+				Some(level) => (c, letters[level].0),
+				None => (c, '?')
 			})
-		.collect()
+			.collect())
+	}
+
+	/// Builds the partial substitution key given by the rank-order association
+	/// of the given text's frequency with the language's one.
+	///
+	/// Since a key never maps two letters to a same plain one, the letters
+	/// that share their plain letter with others are left unknown, along with
+	/// the ones left unknown by the association and the ones outside of the
+	/// latin alphabet.
+	pub fn frequency_key(&self, text: &str, freq: &[(char, f64)]) -> Result<SubstitutionKey, Error> {
+		association_key(&self.associate_frequency(text, freq)?)
 	}
 
 	/// Assembles a result by replacing, over the original text, all the
-	/// corresponding characters from a previos association to the `English`'s
+	/// corresponding letters from a previos association to the language's
 	/// frequency analysis.
+	///
+	/// The layout of the original text is kept as is: only its letters are
+	/// replaced, preserving their case. Every character is normalized by the
+	/// alphabet before looking it up, so the accented forms of a letter are
+	/// replaced too.
 	pub fn assemble_result(
 		&self,
 		text: &str,
-		text_association: &[(&char, &char)]) -> String
+		text_association: &[(char, char)]) -> Result<String, Error>
 	{
-		let patterns: Vec<_> = text_association
-			.iter()
-			.map(|(&c, _)| c.to_string())
-			.collect();
+		let mut replacement: HashMap<char, char> = HashMap::new();
+		for &(c, r) in text_association.iter() {
+			// replacing anything but letters would break the layout of the text
+			if !c.is_alphabetic() {
+				return Err(Error::UnsupportedSymbol(c));
+			}
+			replacement.insert(fold_case(c), fold_case(r));
+		}
 
-		let replacement: Vec<_> = text_association
-			.iter()
-			.map(|(_, &c)| c.to_string())
-			.collect();
+		Ok(text
+			.chars()
+			.map(|c| {
+				self.alphabet
+					.normalize(c)
+					.and_then(|n| replacement.get(&n))
+					.map_or(c, |&r| match_case(r, c))
+			})
+			.collect())
+	}
+
+	/// Builds a table, indexed by the position of each letter in the alphabet,
+	/// with the language's frequency analysis values normalized to add up to 1.
+	fn letters_table(&self) -> [f64; ALPHABET_LEN] {
+		let mut table = [0.0; ALPHABET_LEN];
+		let letters = self.model.letters();
+		let total: f64 = letters.iter().map(|(_, f)| f).sum();
+		for (c, f) in letters.iter() {
+			if c.is_ascii_uppercase() {
+				table[(*c as u8 - b'A') as usize] = f / total;
+			}
+		}
+
+		table
+	}
+}
 
-		let ac = AhoCorasick::new(&patterns);
-		ac.replace_all(text, &replacement)
+/// Retrieves the position in the alphabet of every letter inside the given
+/// text, skipping the rest of the characters.
+pub(crate) fn letter_indices(text: &str) -> Vec<u8> {
+	text
+		.chars()
+		.filter(char::is_ascii_alphabetic)
+		.map(|c| c.to_ascii_uppercase() as u8 - b'A')
+		.collect()
+}
+
+/// Same as [`letter_indices`], but failing when the text has no letters at
+/// all, since there is nothing to analyze then.
+pub(crate) fn require_letters(text: &str) -> Result<Vec<u8>, Error> {
+	match letter_indices(text) {
+		letters if letters.is_empty() => Err(Error::EmptyInput),
+		letters => Ok(letters)
 	}
 }
 
+/// Counts how many times each letter of the alphabet appears inside the
+/// given text, ignoring its case.
+pub(crate) fn letter_counts(text: &str) -> [usize; ALPHABET_LEN] {
+	let mut counts = [0; ALPHABET_LEN];
+	for c in letter_indices(text) { counts[c as usize] += 1; }
+
+	counts
+}
+
+/// Calculates the chi-squared statistic between the given letter counts and
+/// the expected probability of each one of the letters.
+pub(crate) fn chi_squared(counts: &[usize; ALPHABET_LEN], expected: &[f64; ALPHABET_LEN]) -> f64 {
+	let total: usize = counts.iter().sum();
+	counts
+		.iter()
+		.zip(expected.iter())
+		.filter(|(_, &p)| p > 0.0)
+		.map(|(&o, &p)| {
+			let e = p * total as f64;
+			(o as f64 - e).powi(2) / e
+		})
+		.sum()
+}
+
+/// Sorts the given candidates from the best to the worst score and assigns
+/// each one its confidence.
+///
+/// The confidence is computed as the relative likelihood of each candidate,
+/// `exp(-(score - best) * scale)`, normalized over all of them, where `scale`
+/// converts the solver's score into a natural log-likelihood.
+pub(crate) fn rank_candidates(candidates: &mut [Candidate], scale: f64) {
+	candidates.sort_by(|a, b| a.score.total_cmp(&b.score));
+	let best = match candidates.first() {
+		Some(c) => c.score,
+		None => return
+	};
+
+	let weights: Vec<_> = candidates
+		.iter()
+		.map(|c| (-(c.score - best) * scale).exp())
+		.collect();
+	let total: f64 = weights.iter().sum();
+	for (c, w) in candidates.iter_mut().zip(weights) {
+		c.confidence = w / total;
+	}
+}
+
+/// Builds the partial substitution key given by a rank-order association,
+/// leaving unknown the letters that share their plain letter with others,
+/// along with the ones outside of the latin alphabet.
+pub(crate) fn association_key(associations: &[(char, char)]) -> Result<SubstitutionKey, Error> {
+	let shared = |p: char| associations.iter().filter(|(_, q)| *q == p).count() > 1;
+	let latin: Vec<_> = associations
+		.iter()
+		.filter(|(c, p)| c.is_ascii_uppercase() && p.is_ascii_uppercase() && !shared(*p))
+		.copied()
+		.collect();
+
+	SubstitutionKey::from_associations(&latin)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use super::model::Language;
+	use super::shift::shift_text;
 
 	#[test]
 	fn english_freqs_are_retrieved() {
-		let analizer: Analizer = Analizer::new();
-		let eng_freqs = analizer.get_english_freqs();
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
+		let eng_freqs = analizer.get_language_freqs();
 
-		assert_eq!(&analizer._eng_freq, eng_freqs)
+		assert_eq!(analizer.model.letters(), eng_freqs);
+		assert_eq!(eng_freqs[0], ('E', 11.160));
 	}
 	#[test]
 	fn frequency_is_calculated() {
-		let analizer: Analizer = Analizer::new();
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
 		let text = String::from("TEQQEE");
 
-		analizer.calculate_frequency(&text.chars().collect::<Vec<_>>());
+		let freq = analizer.calculate_frequency(&text.chars().collect::<Vec<_>>()).unwrap();
+
+		assert_eq!(freq, vec![('E', 0.5), ('Q', 1.0 / 3.0), ('T', 1.0 / 6.0)]);
+	}
+
+	#[test]
+	fn frequency_ignores_case_and_symbols() {
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
+		let text = String::from("Te qq, EE!");
+
+		let freq = analizer.calculate_frequency(&text.chars().collect::<Vec<_>>()).unwrap();
+
+		assert_eq!(freq, vec![('E', 0.5), ('Q', 1.0 / 3.0), ('T', 1.0 / 6.0)]);
 	}
 
 	#[test]
 	fn frequency_is_associated() {
-		let analizer: Analizer = Analizer::new();
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
 		let text = String::from("AAACCENTM");
 		let chars = &text.chars().collect::<Vec<_>>();
-		let freq = analizer.calculate_frequency(chars);
+		let freq = analizer.calculate_frequency(chars).unwrap();
+		let ass_freqs = analizer.associate_frequency(&text, &freq).unwrap();
+
+		assert_eq!(ass_freqs[0], ('A', 'E'));
+	}
+
+	#[test]
+	fn frequency_key_is_built() {
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
+		let text = String::from("TEBKFKQEBZLROPBLCERJXKBSBKQP");
+		let text_chars: Vec<_> = text.chars().collect();
+		let freq = analizer.calculate_frequency(&text_chars).unwrap();
+
+		// Q and L are both associated with I, so neither keeps it
+		let key = analizer.frequency_key(&text, &freq).unwrap();
+
+		assert_eq!(key.apply("BEK"), "ERA");
+		assert_eq!((key.get(b'Q' - b'A'), key.get(b'L' - b'A'), key.get(b'T' - b'A')), (None, None, None));
 	}
 
 	#[test]
 	fn result_is_propertly_assembled() {
-		let analizer: Analizer = Analizer::new();
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
 		let text = String::from("TEBKFKQEBZLROPBLCERJXKBSBKQP");
 		let text_chars: Vec<_> = text.chars().collect();
-		let freq = analizer.calculate_frequency(&text_chars);
-		println!("{freq:?}");
-		let ass_freqs = analizer.associate_frequency(&text, &freq);
-		println!("{ass_freqs:?}");
+		let freq = analizer.calculate_frequency(&text_chars).unwrap();
+		let ass_freqs = analizer.associate_frequency(&text, &freq).unwrap();
+
+		let res = analizer.assemble_result(&text, &ass_freqs).unwrap();
+
+		assert_eq!(res, String::from("?REA?AIRE?II?IEI?RI??AE?EAII"))
+	}
+
+	#[test]
+	fn repeated_letters_are_assembled() {
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
+		let text = String::from("QQQQXXXZZM");
+		let text_chars: Vec<_> = text.chars().collect();
+		let freq = analizer.calculate_frequency(&text_chars).unwrap();
+		let ass_freqs = analizer.associate_frequency(&text, &freq).unwrap();
+
+		let res = analizer.assemble_result(&text, &ass_freqs).unwrap();
+
+		assert_eq!(res, String::from("EEEEAAARR?"))
+	}
+
+	#[test]
+	fn layout_is_preserved() {
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
+		let text = String::from("Qqq qX,\n xXz-Z... m");
+		let text_chars: Vec<_> = text.chars().collect();
+		let freq = analizer.calculate_frequency(&text_chars).unwrap();
+		let ass_freqs = analizer.associate_frequency(&text, &freq).unwrap();
+
+		let res = analizer.assemble_result(&text, &ass_freqs).unwrap();
+
+		assert_eq!(res, String::from("Eee eA,\n aAr-R... ?"))
+	}
+
+	/// Runs the frequency, association and assembly steps over the text.
+	fn analyze(analizer: &Analizer, text: &str) -> String {
+		let freq = analizer.calculate_frequency(&text.chars().collect::<Vec<_>>()).unwrap();
+		let ass_freqs = analizer.associate_frequency(text, &freq).unwrap();
+
+		analizer.assemble_result(text, &ass_freqs).unwrap()
+	}
+
+	#[test]
+	fn latin_scripts_are_analyzed() {
+		let spanish = Analizer::new(LanguageModel::bundled(Language::Spanish));
+		let german = Analizer::new(LanguageModel::bundled(Language::German));
+
+		assert_eq!(analyze(&spanish, "ñÑñ aá, n"), "eEe aa, ?");
+		assert_eq!(analyze(&german, "ßßß Ää x"), "eee Nn ?");
+	}
+
+	#[test]
+	fn greek_is_analyzed() {
+		let model = LanguageModel::parse("[letters]\nΑ 12\nΟ 10\nΣ 8\nΞ 1").unwrap();
+		let analizer = Analizer::new(model);
+
+		assert_eq!(analizer.get_alphabet().letters(), &['Α', 'Ο', 'Σ', 'Ξ']);
+		assert_eq!(analyze(&analizer, "Σσς, ξξ ά!"), "Ααα, οο ?!");
+	}
+
+	#[test]
+	fn cyrillic_is_analyzed() {
+		let model = LanguageModel::parse("[letters]\nО 11\nЕ 8\nЖ 1\nЁ 1").unwrap();
+		let analizer = Analizer::new(model);
+
+		assert_eq!(analyze(&analizer, "Жжж, ёё!"), "Ооо, ее!");
+	}
+
+	#[test]
+	fn ngram_frequency_is_calculated() {
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
+		let text: Vec<_> = "The theme, then.".chars().collect();
+		let freq = analizer.calculate_ngram_frequency(&text, 3).unwrap();
+
+		assert_eq!(freq[0], ("THE".to_owned(), 3.0 / 10.0));
+		assert!((freq.iter().map(|(_, f)| f).sum::<f64>() - 1.0).abs() < 1e-9);
+		assert!(matches!(analizer.calculate_ngram_frequency(&text[..3], 4), Err(Error::EmptyInput)));
+	}
+
+	#[test]
+	fn invalid_input_is_rejected() {
+		let model = LanguageModel::parse("[letters]\nE 2\nT 1").unwrap();
+		let analizer: Analizer = Analizer::with_alphabet(model, Alphabet::latin());
+		let text = String::from("AAAABBBCC");
+		let freq = analizer.calculate_frequency(&text.chars().collect::<Vec<_>>()).unwrap();
+
+		assert!(matches!(analizer.calculate_frequency(&['1', ' ']), Err(Error::EmptyInput)));
+		assert!(matches!(analizer.associate_frequency("", &[]), Err(Error::EmptyInput)));
+		assert!(matches!(
+			analizer.associate_frequency(&text, &freq),
+			Err(Error::AlphabetOverflow { levels: 3, letters: 2 })
+		));
+		assert!(matches!(analizer.assemble_result(&text, &[(' ', 'E')]), Err(Error::UnsupportedSymbol(' '))));
+		assert!(matches!(analizer.crack_shift("42!"), Err(Error::EmptyInput)));
+	}
+
+	#[test]
+	fn shift_is_cracked() {
+		let analizer: Analizer = Analizer::new(LanguageModel::default());
+		let plain = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG AND KEEPS RUNNING \
+			THROUGH THE FOREST UNTIL IT REACHES THE RIVER";
+		let cyphered = shift_text(plain, 7);
 
-		let res = analizer.assemble_result(&text, &ass_freqs);
+		let candidates = analizer.crack_shift(&cyphered).unwrap();
 
-		assert_eq!(res, String::from("ABC"))
+		assert_eq!(candidates.len(), ALPHABET_LEN);
+		assert_eq!(candidates[0].text, plain);
+		assert_eq!(candidates[0].key, "7 (H)");
+		assert!(candidates[0].confidence > 0.9);
 	}
 }
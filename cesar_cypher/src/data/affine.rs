@@ -0,0 +1,53 @@
+use crate::{cipher::{Affine, Cipher}, error::Error};
+
+use super::{chi_squared, letter_counts, rank_candidates, require_letters, Analizer, Candidate, ALPHABET_LEN};
+
+impl Analizer {
+	/// Tries every valid key of the affine cypher over the given text and ranks
+	/// the resulting plaintexts by the chi-squared distance between their
+	/// letters distribution and the language's frequency analysis.
+	///
+	/// Only the values of `a` coprime with the length of the alphabet produce
+	/// a reversible cypher, which leaves 12 * 26 = 312 keys to try.
+	pub fn crack_affine(&self, text: &str) -> Result<Vec<Candidate>, Error> {
+		require_letters(text)?;
+		let expected = self.letters_table();
+		let mut candidates: Vec<_> = (1..ALPHABET_LEN as u8)
+			.flat_map(|a| (0..ALPHABET_LEN as u8).map(move |b| (a, b)))
+			.filter_map(|(a, b)| Affine::new(a, b).ok().map(|cipher| (a, b, cipher)))
+			.map(|(a, b, cipher)| {
+				let plain = cipher.decrypt(text);
+				Candidate {
+					key: format!("{a}/{b}"),
+					score: chi_squared(&letter_counts(&plain), &expected),
+					text: plain,
+					confidence: 0.0
+				}
+			})
+			.collect();
+
+		rank_candidates(&mut candidates, 0.5);
+
+		Ok(candidates)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::model::LanguageModel;
+
+	#[test]
+	fn affine_is_cracked() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let plain = "THE AFFINE CYPHER MULTIPLIES EVERY LETTER BY A NUMBER AND THEN ADDS \
+			ANOTHER ONE TO IT, WHICH LEAVES ONLY A FEW HUNDRED KEYS TO TRY";
+		let cyphered = Affine::new(7, 3).unwrap().encrypt(plain);
+
+		let candidates = analizer.crack_affine(&cyphered).unwrap();
+
+		assert_eq!(candidates.len(), 312);
+		assert_eq!(candidates[0].key, "7/3");
+		assert_eq!(candidates[0].text, plain);
+	}
+}
@@ -0,0 +1,187 @@
+use unicode_normalization::UnicodeNormalization;
+
+use super::model::LanguageModel;
+
+/// This struct represents the set of letters a text is analyzed over, along
+/// with the rules that turn every character of the text into one of them.
+///
+/// The characters are normalized by folding their case into the uppercase
+/// form and, when accent stripping is enabled, by dropping the diacritics of
+/// the ones that are not letters of the alphabet on their own. So a Spanish
+/// alphabet keeps `Ñ` apart from `N`, while `Á` is counted as an `A`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+	letters: Vec<char>,
+	strip_accents: bool
+}
+
+impl Alphabet {
+	/// Creates an alphabet made of the given letters, in the given order and
+	/// without repetitions. Accent stripping is enabled by default.
+	pub fn new<I: IntoIterator<Item = char>>(letters: I) -> Self {
+		let mut unique: Vec<char> = Vec::new();
+		for c in letters.into_iter().map(fold_case) {
+			if c.is_alphabetic() && !unique.contains(&c) { unique.push(c); }
+		}
+
+		Self { letters: unique, strip_accents: true }
+	}
+
+	/// Creates the alphabet made of the letters of the given language model.
+	pub fn from_model(model: &LanguageModel) -> Self {
+		Self::new(model.letters().iter().map(|(c, _)| *c))
+	}
+
+	/// The 26 letters of the latin alphabet.
+	pub fn latin() -> Self {
+		Self::new('A'..='Z')
+	}
+
+	/// Enables or disables the accent stripping of the characters that are
+	/// not letters of the alphabet.
+	pub fn strip_accents(mut self, strip: bool) -> Self {
+		self.strip_accents = strip;
+		self
+	}
+
+	/// Retrieves the letters of the alphabet.
+	pub fn letters(&self) -> &[char] {
+		&self.letters
+	}
+
+	/// Determines whether the given character is a letter of the alphabet once
+	/// normalized.
+	pub fn contains(&self, c: char) -> bool {
+		self.normalize(c).is_some()
+	}
+
+	/// Turns the given character into the letter of the alphabet it stands
+	/// for, if any.
+	pub fn normalize(&self, c: char) -> Option<char> {
+		let folded = fold_case(c);
+		if self.letters.contains(&folded) {
+			return Some(folded);
+		}
+		if !self.strip_accents {
+			return None;
+		}
+
+		let base = strip_accent(folded);
+		self.letters.contains(&base).then_some(base)
+	}
+}
+
+impl Default for Alphabet {
+	fn default() -> Self {
+		Self::latin()
+	}
+}
+
+/// Folds the case of the given letter into its uppercase form, as long as it
+/// has a single character one.
+///
+/// The German `ß` is an exception: its uppercase form is `SS`, so it is folded
+/// into the capital `ẞ` instead.
+pub(crate) fn fold_case(c: char) -> char {
+	if c == 'ß' {
+		return 'ẞ';
+	}
+
+	let mut upper = c.to_uppercase();
+	match (upper.next(), upper.next()) {
+		(Some(u), None) => u,
+		_ => c
+	}
+}
+
+/// Drops the diacritics of the given letter.
+pub(crate) fn strip_accent(c: char) -> char {
+	// the canonical decomposition places the base letter first, followed by
+	// its combining marks
+	c.nfd().next().map_or(c, fold_case)
+}
+
+/// Turns the given letter into the case of the original character it
+/// replaces, as long as it has a single character lowercase form.
+pub(crate) fn match_case(letter: char, original: char) -> char {
+	if !original.is_lowercase() {
+		return letter;
+	}
+
+	let mut lower = letter.to_lowercase();
+	match (lower.next(), lower.next()) {
+		(Some(l), None) => l,
+		_ => letter
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The 24 letters of the greek alphabet.
+	fn greek() -> Alphabet {
+		Alphabet::new(('Α'..='Ω').filter(|&c| c != '\u{3a2}'))
+	}
+
+	/// The 33 letters of the russian cyrillic alphabet.
+	fn cyrillic() -> Alphabet {
+		let mut letters: Vec<char> = ('А'..='Я').collect();
+		letters.insert(6, 'Ё');
+
+		Alphabet::new(letters)
+	}
+
+	#[test]
+	fn spanish_is_normalized() {
+		let spanish = Alphabet::latin().letters().iter().copied().chain(['Ñ']).collect::<Vec<_>>();
+		let alphabet = Alphabet::new(spanish);
+
+		assert_eq!(alphabet.normalize('ñ'), Some('Ñ'));
+		assert_eq!(alphabet.normalize('á'), Some('A'));
+		assert_eq!(alphabet.normalize('Ü'), Some('U'));
+		assert_eq!(alphabet.clone().strip_accents(false).normalize('á'), None);
+		assert_eq!(alphabet.normalize('¿'), None);
+	}
+
+	#[test]
+	fn german_is_normalized() {
+		let alphabet = Alphabet::new(('A'..='Z').chain(['Ä', 'Ö', 'Ü', 'ß']));
+
+		assert_eq!(alphabet.letters().len(), 30);
+		assert_eq!(alphabet.normalize('ß'), Some('ẞ'));
+		assert_eq!(alphabet.normalize('ẞ'), Some('ẞ'));
+		assert_eq!(alphabet.normalize('ö'), Some('Ö'));
+		assert_eq!(alphabet.normalize('é'), Some('E'));
+	}
+
+	#[test]
+	fn greek_is_normalized() {
+		let alphabet = greek();
+
+		assert_eq!(alphabet.letters().len(), 24);
+		assert_eq!(alphabet.normalize('ς'), Some('Σ'));
+		assert_eq!(alphabet.normalize('ά'), Some('Α'));
+		assert_eq!(alphabet.normalize('ΐ'), Some('Ι'));
+		assert_eq!(alphabet.normalize('a'), None);
+	}
+
+	#[test]
+	fn cyrillic_is_normalized() {
+		let alphabet = cyrillic();
+
+		assert_eq!(alphabet.letters().len(), 33);
+		assert_eq!(alphabet.normalize('ё'), Some('Ё'));
+		assert_eq!(alphabet.normalize('й'), Some('Й'));
+		assert_eq!(alphabet.normalize('я'), Some('Я'));
+		assert_eq!(alphabet.normalize('ї'), None);
+	}
+
+	#[test]
+	fn case_is_matched() {
+		assert_eq!(match_case('Σ', 'ς'), 'σ');
+		assert_eq!(match_case('ẞ', 'ß'), 'ß');
+		assert_eq!(match_case('E', 'Q'), 'E');
+		assert_eq!(match_case('?', 'q'), '?');
+	}
+}
@@ -0,0 +1,248 @@
+use std::{collections::HashMap, io::{self, Read}, str};
+
+use rayon::prelude::*;
+
+use crate::error::Error;
+
+use super::Analizer;
+
+/// Size of the chunks the text is read in.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Minimum size of the slices of a chunk counted by each thread.
+const MIN_SLICE_SIZE: usize = 1 << 16;
+
+/// This struct represents the raw counts of the characters of a text, before
+/// being normalized into the letters of an alphabet.
+///
+/// Since the normalization of a character does not depend on its context, the
+/// characters are counted as they are, and every distinct one is normalized a
+/// single time once the whole text has been counted.
+#[derive(Debug, Clone)]
+pub(super) struct Counts {
+	ascii: [usize; 128],
+	others: HashMap<char, usize>
+}
+
+impl Counts {
+	pub(super) fn new() -> Self {
+		Self { ascii: [0; 128], others: HashMap::new() }
+	}
+
+	/// Counts the characters of the given text.
+	pub(super) fn add(&mut self, text: &str) {
+		for c in text.chars() {
+			match c.is_ascii() {
+				true => self.ascii[c as usize] += 1,
+				false => *self.others.entry(c).or_insert(0) += 1
+			}
+		}
+	}
+
+	/// Counts the characters of the given text, splitting it into slices that
+	/// are counted in parallel.
+	fn par_add(&mut self, text: &str) {
+		let slices = split(text, rayon::current_num_threads());
+		let counts = slices
+			.into_par_iter()
+			.map(|slice| {
+				let mut counts = Self::new();
+				counts.add(slice);
+				counts
+			})
+			.reduce(Self::new, |mut a, b| {
+				a.merge(b);
+				a
+			});
+
+		self.merge(counts);
+	}
+
+	fn merge(&mut self, other: Self) {
+		for (n, m) in self.ascii.iter_mut().zip(other.ascii) {
+			*n += m;
+		}
+		for (c, m) in other.others {
+			*self.others.entry(c).or_insert(0) += m;
+		}
+	}
+
+	pub(super) fn iter(&self) -> impl Iterator<Item = (char, usize)> + '_ {
+		(0..128u8)
+			.map(|b| (b as char, self.ascii[b as usize]))
+			.chain(self.others.iter().map(|(&c, &n)| (c, n)))
+			.filter(|&(_, n)| n > 0)
+	}
+}
+
+/// Splits the given text into the given amount of slices at most, cutting it
+/// only at the boundaries of its characters.
+fn split(text: &str, amount: usize) -> Vec<&str> {
+	let size = (text.len() / amount.max(1)).max(MIN_SLICE_SIZE);
+	let mut slices = Vec::new();
+	let mut rest = text;
+	while rest.len() > size {
+		let mut end = size;
+		while !rest.is_char_boundary(end) { end += 1; }
+		let (slice, tail) = rest.split_at(end);
+		slices.push(slice);
+		rest = tail;
+	}
+	slices.push(rest);
+
+	slices
+}
+
+impl Analizer {
+	/// Determines the frequency of each letter of the text read from the given
+	/// reader, with the same result as `calculate_frequency`.
+	///
+	/// The text is read and counted in chunks, so it never needs to be loaded
+	/// at once, and every chunk is counted in parallel when told so. The text
+	/// must be valid UTF-8.
+	pub fn stream_frequency<R: Read>(&self, reader: R, parallel: bool) -> Result<Vec<(char, f64)>, Error> {
+		self.stream_letters(reader, parallel).map(|(freq, _)| freq)
+	}
+
+	/// Same as [`Analizer::stream_frequency`], but also giving back the amount
+	/// of letters of the text, which is otherwise lost once it is read.
+	pub(crate) fn stream_letters<R: Read>(
+		&self,
+		mut reader: R,
+		parallel: bool
+	) -> Result<(Vec<(char, f64)>, usize), Error>
+	{
+		let mut counts = Counts::new();
+		let mut buffer = vec![0; CHUNK_SIZE];
+		// amount of bytes of an incomplete character left at the end of the
+		// last chunk, which are moved to the start of the buffer
+		let mut pending = 0;
+		loop {
+			let read = match reader.read(&mut buffer[pending..]) {
+				Ok(0) => break,
+				Ok(read) => read,
+				Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e.into())
+			};
+
+			let filled = pending + read;
+			let valid = match str::from_utf8(&buffer[..filled]) {
+				Ok(text) => text,
+				// the chunk ends in the middle of a character, so its first
+				// bytes are left for the next one
+				Err(e) if e.error_len().is_none() => {
+					str::from_utf8(&buffer[..e.valid_up_to()]).unwrap_or_default()
+				},
+				Err(e) => return Err(invalid_data(e))
+			};
+			match parallel {
+				true => counts.par_add(valid),
+				false => counts.add(valid)
+			}
+
+			let valid_len = valid.len();
+			buffer.copy_within(valid_len..filled, 0);
+			pending = filled - valid_len;
+		}
+		if pending > 0 {
+			return Err(invalid_data(str::from_utf8(&buffer[..pending]).unwrap_err()));
+		}
+
+		self.frequency_of_counts(&counts)
+	}
+
+	/// Turns the raw counts of the characters of a text into the frequency of
+	/// each letter of the alphabet, counted into a fixed table, along with the
+	/// amount of letters.
+	fn frequency_of_counts(&self, counts: &Counts) -> Result<(Vec<(char, f64)>, usize), Error> {
+		let letters = self.alphabet.letters();
+		let mut table = vec![0usize; letters.len()];
+		for (c, n) in counts.iter() {
+			let Some(letter) = self.alphabet.normalize(c) else { continue };
+			if let Some(i) = letters.iter().position(|&l| l == letter) {
+				table[i] += n;
+			}
+		}
+
+		let text_len: usize = table.iter().sum();
+		if text_len == 0 {
+			return Err(Error::EmptyInput);
+		}
+
+		let mut freq_vec: Vec<_> = letters
+			.iter()
+			.zip(table)
+			.filter(|&(_, n)| n > 0)
+			.map(|(&c, n)| (c, n as f64 / text_len as f64))
+			.collect();
+		freq_vec.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+		Ok((freq_vec, text_len))
+	}
+}
+
+fn invalid_data(e: str::Utf8Error) -> Error {
+	io::Error::new(io::ErrorKind::InvalidData, e).into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{model::LanguageModel, Alphabet};
+
+	/// A reader that hands its text a few bytes at a time, so the characters
+	/// are split across the chunks.
+	struct Trickle<'a>(&'a [u8]);
+
+	impl Read for Trickle<'_> {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			let n = buf.len().min(self.0.len()).min(3);
+			buf[..n].copy_from_slice(&self.0[..n]);
+			self.0 = &self.0[n..];
+			Ok(n)
+		}
+	}
+
+	#[test]
+	fn streamed_frequency_matches() {
+		let model = LanguageModel::parse("[letters]\nE 50\nÑ 25\nA 25").unwrap();
+		let analizer = Analizer::with_alphabet(model, Alphabet::new(('A'..='Z').chain(['Ñ'])));
+		let text = "Añada España, ¡señor! ".repeat(5000);
+
+		let expected = analizer.calculate_frequency(&text.chars().collect::<Vec<_>>()).unwrap();
+
+		assert_eq!(analizer.stream_frequency(text.as_bytes(), false).unwrap(), expected);
+		assert_eq!(analizer.stream_frequency(text.as_bytes(), true).unwrap(), expected);
+		assert_eq!(analizer.stream_frequency(Trickle(text.as_bytes()), false).unwrap(), expected);
+	}
+
+	#[test]
+	fn streamed_letters_are_associated() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let text = "Añada España, ¡señor! The quick brown fox jumps over the lazy dog. ".repeat(500);
+
+		let freq = analizer.stream_frequency(text.as_bytes(), false).unwrap();
+		let (streamed, letters) = analizer.stream_letters(text.as_bytes(), true).unwrap();
+
+		assert_eq!(streamed, freq);
+		assert_eq!(analizer.associate_levels(letters, &freq).unwrap(), analizer.associate_frequency(&text, &freq).unwrap());
+	}
+
+	#[test]
+	fn invalid_streams_are_rejected() {
+		let analizer = Analizer::new(LanguageModel::default());
+
+		assert!(matches!(analizer.stream_frequency("1 2 3".as_bytes(), true), Err(Error::EmptyInput)));
+		assert!(matches!(analizer.stream_frequency(&b"abc\xff"[..], false), Err(Error::Io(_))));
+		assert!(matches!(analizer.stream_frequency(&b"abc\xc3"[..], false), Err(Error::Io(_))));
+	}
+
+	#[test]
+	fn slices_keep_characters_whole() {
+		let text = "ñ".repeat(MIN_SLICE_SIZE);
+		let slices = split(&text, 4);
+
+		assert!(slices.len() > 1);
+		assert_eq!(slices.concat(), text);
+	}
+}
@@ -0,0 +1,162 @@
+use crate::error::Error;
+
+use super::{fitness::NgramFitness, letter_indices, Analizer, Candidate, Variant};
+
+/// A solver able to recover the plain text of a cyphered one.
+///
+/// Every solver of the `Analizer` is exposed through this trait, so the
+/// callers can try several of them over a same text and compare their
+/// results, which are always given as candidates ranked from the most to the
+/// least likely.
+pub trait Cracker {
+	/// Cracks the given text with the help of the given analizer.
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error>;
+}
+
+/// Brute-forces every rotation of a Caesar cypher.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShiftCracker;
+
+impl Cracker for ShiftCracker {
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error> {
+		analizer.crack_shift(text)
+	}
+}
+
+/// Brute-forces every valid key of an affine cypher.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AffineCracker;
+
+impl Cracker for AffineCracker {
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error> {
+		analizer.crack_affine(text)
+	}
+}
+
+/// Associates the frequency of the letters of the text with the language's
+/// one by rank, and completes the association into the given amount of full
+/// decryptions.
+#[derive(Debug, Clone, Copy)]
+pub struct FrequencyCracker {
+	pub top: usize
+}
+
+impl Cracker for FrequencyCracker {
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error> {
+		let freq = analizer.stream_frequency(text.as_bytes(), false)?;
+		let key = analizer.frequency_key(text, &freq)?;
+
+		analizer.rank_decryptions(text, &key, self.top)
+	}
+}
+
+/// Searches the whole key of a monoalphabetic substitution cypher, restarting
+/// the search from random keys the given amount of times.
+#[derive(Debug, Clone, Copy)]
+pub struct SubstitutionCracker {
+	pub restarts: usize
+}
+
+impl Cracker for SubstitutionCracker {
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error> {
+		let solution = analizer.crack_substitution(text, self.restarts)?;
+
+		Ok(vec![fitness_candidate(analizer, solution.key.to_string(), solution.text)])
+	}
+}
+
+/// Searches the key of a homophonic substitution cypher, restarting the search
+/// from random keys the given amount of times.
+#[derive(Debug, Clone, Copy)]
+pub struct HomophonicCracker {
+	pub restarts: usize
+}
+
+impl Cracker for HomophonicCracker {
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error> {
+		let solution = analizer.crack_homophonic(text, self.restarts)?;
+		Ok(vec![fitness_candidate(analizer, solution.key(), solution.text)])
+	}
+}
+
+/// Recovers the keyword of a polyalphabetic cypher of the given variant.
+#[derive(Debug, Clone, Copy)]
+pub struct PolyalphabeticCracker {
+	pub variant: Variant,
+	pub max_key_len: usize
+}
+
+impl Cracker for PolyalphabeticCracker {
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error> {
+		let solution = analizer.crack_polyalphabetic(text, self.variant, self.max_key_len)?;
+
+		Ok(vec![fitness_candidate(analizer, solution.keyword, solution.text)])
+	}
+}
+
+/// Tries every amount of rails and offset of a rail-fence cypher, up to the
+/// given amount of rails.
+#[derive(Debug, Clone, Copy)]
+pub struct RailFenceCracker {
+	pub max_rails: usize
+}
+
+impl Cracker for RailFenceCracker {
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error> {
+		analizer.crack_rail_fence(text, self.max_rails)
+	}
+}
+
+/// Searches the order of the columns of a columnar transposition for every
+/// key length up to the given maximum.
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnarCracker {
+	pub max_key_len: usize,
+	pub restarts: usize
+}
+
+impl Cracker for ColumnarCracker {
+	fn crack(&self, analizer: &Analizer, text: &str) -> Result<Vec<Candidate>, Error> {
+		analizer.crack_columnar(text, self.max_key_len, self.restarts)
+	}
+}
+
+/// Builds the single candidate of a solver that only gives back its best
+/// solution, scored by its n-gram fitness per letter, negated so the lower,
+/// the better.
+fn fitness_candidate(analizer: &Analizer, key: String, text: String) -> Candidate {
+	let letters = letter_indices(&text);
+	let score = -NgramFitness::new(&analizer.model).score(&letters) / letters.len().max(1) as f64;
+
+	Candidate { key, text, score, confidence: 1.0 }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{model::LanguageModel, shift::shift_text};
+
+	const PLAIN: &str = "It is a truth universally acknowledged, that a single man in possession \
+		of a good fortune, must be in want of a wife. However little known the feelings or views \
+		of such a man may be on his first entering a neighbourhood, this truth is so well fixed \
+		in the minds of the surrounding families, that he is considered as the rightful property \
+		of some one or other of their daughters.";
+
+	#[test]
+	fn crackers_are_interchangeable() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = shift_text(PLAIN, 3);
+		let crackers: [Box<dyn Cracker>; 3] = [
+			Box::new(ShiftCracker),
+			Box::new(AffineCracker),
+			Box::new(PolyalphabeticCracker { variant: Variant::Vigenere, max_key_len: 8 })
+		];
+
+		for cracker in crackers.iter() {
+			let candidates = cracker.crack(&analizer, &cyphered).unwrap();
+
+			assert_eq!(candidates[0].text, PLAIN);
+			assert!(candidates.windows(2).all(|w| w[0].score <= w[1].score));
+		}
+	}
+}
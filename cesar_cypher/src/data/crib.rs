@@ -0,0 +1,254 @@
+use std::{f64::consts::LN_10, fmt::Display};
+
+use rand::Rng;
+
+use crate::error::Error;
+
+use super::{key::SubstitutionKey, rank_candidates, Analizer, Candidate, ALPHABET_LEN};
+
+/// The keys the placements of a crib are checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CribKey {
+	/// Every letter is shifted by a same amount of positions.
+	Shift,
+	/// Every letter is replaced by a same letter along the whole text.
+	Substitution
+}
+
+impl Display for CribKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			Self::Shift => "shift",
+			Self::Substitution => "substitution"
+		};
+
+		write!(f, "{name}")
+	}
+}
+
+/// This struct represents a position of a known word of the plain text
+/// inside the cyphered one that is consistent with the key, along with the
+/// decryption the key was completed into.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribPlacement {
+	/// The position of the crib among the letters of the text.
+	pub position: usize,
+	/// The partial key given by the cyphered letters covered by the crib and
+	/// their plain ones.
+	pub mappings: SubstitutionKey,
+	/// The decryption given by the completed key.
+	pub candidate: Candidate
+}
+
+impl Analizer {
+	/// Slides the given crib across the letters of the given text, keeping the
+	/// positions where it does not conflict with a key of the given kind,
+	/// from the most to the least likely one.
+	///
+	/// A position conflicts with a substitution when a letter would have to
+	/// stand for two different ones, and with a shift when its letters are not
+	/// all shifted by the same amount. Every surviving position fixes the
+	/// letters it implies, so the decryption always holds the crib, and the
+	/// rest of the substitution key is taken from the frequency rank of the
+	/// letters, as done by [`Analizer::frequency_key`], and completed by
+	/// [`Analizer::rank_decryptions`].
+	///
+	/// When restarts are given, the rest of the key is searched by annealing
+	/// instead, as done by [`Analizer::complete_substitution_with`], with up
+	/// to `CRIB_RESTARTS` restarts at every position.
+	pub fn place_crib(
+		&self,
+		text: &str,
+		crib: &str,
+		key: CribKey,
+		restarts: Option<usize>
+	) -> Result<Vec<CribPlacement>, Error>
+	{
+		self.place_crib_with(text, crib, key, restarts, &mut rand::thread_rng())
+	}
+
+	/// Same as [`Analizer::place_crib`], but drawing the random keys of the
+	/// substitution search from the given generator.
+	pub fn place_crib_with<R: Rng + ?Sized>(
+		&self,
+		text: &str,
+		crib: &str,
+		key: CribKey,
+		restarts: Option<usize>,
+		rng: &mut R
+	) -> Result<Vec<CribPlacement>, Error>
+	{
+		let letters: Vec<char> = text.chars().filter_map(|c| self.alphabet.normalize(c)).collect();
+		if letters.is_empty() {
+			return Err(Error::EmptyInput);
+		}
+		let crib: Vec<char> = crib.chars().filter_map(|c| self.alphabet.normalize(c)).collect();
+		if crib.is_empty() {
+			return Err(Error::InvalidCrib("it holds no letter of the alphabet".to_owned()));
+		}
+		if crib.len() > letters.len() {
+			return Err(Error::InvalidCrib("it is longer than the text".to_owned()));
+		}
+
+		let base = match (key, restarts) {
+			(CribKey::Substitution, None) => self.frequency_key(text, &self.stream_frequency(text.as_bytes(), false)?)?,
+			_ => SubstitutionKey::unknown()
+		};
+
+		let mut placements = Vec::new();
+		for position in 0..=letters.len() - crib.len() {
+			let Some(mappings) = consistent_mappings(&letters[position..position + crib.len()], &crib) else {
+				continue
+			};
+
+			let candidate = match key {
+				CribKey::Shift => {
+					let Some(shift) = crib_shift(&mappings) else { continue };
+					let mut candidate = self.rank_decryptions(text, &shift_key(shift), 1)?.remove(0);
+					candidate.key = format!("{shift} ({})", (b'A' + shift) as char);
+					candidate
+				},
+				CribKey::Substitution => match restarts {
+					Some(restarts) => {
+						let solution = self.complete_substitution_with(text, &mappings, restarts.min(CRIB_RESTARTS), rng)?;
+						Candidate {
+							key: solution.key.to_string(),
+							text: solution.text,
+							score: -solution.score / letters.len() as f64,
+							confidence: 0.0
+						}
+					},
+					None => self.rank_decryptions(text, &merge_mappings(&base, &mappings), 1)?.remove(0)
+				}
+			};
+			placements.push(CribPlacement { position, mappings, candidate });
+		}
+
+		// the scores are the fitness per letter, so they are turned back into
+		// log-likelihoods by the length of the text. The placements are sorted
+		// beforehand, so their candidates keep the order they are ranked in
+		placements.sort_by(|a, b| a.candidate.score.total_cmp(&b.candidate.score));
+		let mut candidates: Vec<Candidate> = placements.iter().map(|p| p.candidate.clone()).collect();
+		rank_candidates(&mut candidates, LN_10 * letters.len() as f64);
+		for (p, candidate) in placements.iter_mut().zip(candidates) {
+			p.candidate = candidate;
+		}
+
+		Ok(placements)
+	}
+}
+
+/// Number of annealing restarts the substitution search is allowed at every
+/// position of the crib, which would otherwise take too long.
+const CRIB_RESTARTS: usize = 2;
+
+/// Builds the key that holds the given mappings over the given one, leaving
+/// unknown the letters that held any of their plain letters.
+fn merge_mappings(key: &SubstitutionKey, mappings: &SubstitutionKey) -> SubstitutionKey {
+	let mut merged = key.clone();
+	for c in 0..ALPHABET_LEN as u8 {
+		let Some(p) = mappings.get(c) else { continue };
+		if let Some(o) = merged.inverse().get(p) {
+			merged.set(o, None);
+		}
+		merged.set(c, Some(p));
+	}
+
+	merged
+}
+
+/// Retrieves the shift shared by all the given mappings, if any.
+fn crib_shift(mappings: &SubstitutionKey) -> Option<u8> {
+	let n = ALPHABET_LEN as u8;
+
+	let mut shifts = (0..n).filter_map(|c| mappings.get(c).map(|p| (c + n - p) % n));
+	let shift = shifts.next()?;
+
+	shifts.all(|s| s == shift).then_some(shift)
+}
+
+/// Builds the key that replaces every letter of the latin alphabet by the
+/// one the given amount of positions behind it.
+fn shift_key(shift: u8) -> SubstitutionKey {
+	let n = ALPHABET_LEN as u8;
+	let mut table = [0; ALPHABET_LEN];
+	for (c, p) in table.iter_mut().enumerate() {
+		*p = (c as u8 + n - shift) % n;
+	}
+
+	SubstitutionKey::from_table(table).expect("a shift is a bijection of the alphabet")
+}
+
+/// Pairs every cyphered letter of the window with the plain letter of the
+/// crib, as long as no letter has to stand for two different ones. Like the
+/// keys, it only covers the latin alphabet.
+fn consistent_mappings(window: &[char], crib: &[char]) -> Option<SubstitutionKey> {
+	let index = |c: char| c.is_ascii_uppercase().then(|| c as u8 - b'A');
+
+	let mut table = [None; ALPHABET_LEN];
+	for (&c, &p) in window.iter().zip(crib.iter()) {
+		let (c, p) = (index(c)?, index(p)?);
+		match table[c as usize] {
+			Some(q) if q != p => return None,
+			_ => table[c as usize] = Some(p)
+		}
+	}
+
+	SubstitutionKey::partial(table)
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{rngs::StdRng, SeedableRng};
+
+	use super::*;
+	use super::super::{model::LanguageModel, shift::shift_text};
+
+	const PLAIN: &str = "Attack the northern bridge at dawn and hold it until the relief arrives";
+
+	#[test]
+	fn conflicting_placements_are_rejected() {
+		assert_eq!(
+			consistent_mappings(&['X', 'Y', 'X'], &['A', 'B', 'A']).map(|k| k.associations()),
+			Some(vec![('X', 'A'), ('Y', 'B')])
+		);
+		assert_eq!(consistent_mappings(&['X', 'Y', 'Y'], &['A', 'B', 'A']), None);
+		assert_eq!(consistent_mappings(&['X', 'Y', 'Z'], &['A', 'B', 'A']), None);
+	}
+
+	#[test]
+	fn crib_recovers_the_shift() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = shift_text(PLAIN, 5);
+
+		let placements = analizer.place_crib(&cyphered, "attack", CribKey::Shift, None).unwrap();
+
+		assert_eq!(placements[0].position, 0);
+		assert_eq!(placements[0].candidate.text, PLAIN);
+		assert_eq!(placements[0].candidate.key, "5 (F)");
+		assert!(placements.iter().all(|p| crib_shift(&p.mappings).is_some()));
+	}
+
+	#[test]
+	fn crib_seeds_the_substitution() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = shift_text(PLAIN, 5);
+
+		let placements = analizer
+			.place_crib_with(&cyphered, "attack", CribKey::Substitution, Some(2), &mut StdRng::seed_from_u64(0))
+			.unwrap();
+		let ranked = analizer.place_crib(&cyphered, "attack", CribKey::Substitution, None).unwrap();
+
+		// the rest of the letters are searched around the ones of the crib, so
+		// every decryption holds it where it was placed
+		assert_eq!(placements[0].position, 0);
+		assert!(placements[0].candidate.text.starts_with("Attack the northern bridge"));
+		assert_eq!(ranked.len(), placements.len());
+		assert!(placements.iter().chain(ranked.iter()).all(|p| {
+			let key: SubstitutionKey = p.candidate.key.parse().unwrap();
+			let mappings = p.mappings.associations();
+			!mappings.is_empty() && mappings.iter().all(|a| key.associations().contains(a))
+		}));
+		assert!(matches!(analizer.place_crib(&cyphered, "42", CribKey::Shift, None), Err(Error::InvalidCrib(_))));
+	}
+}
@@ -0,0 +1,173 @@
+use crate::error::Error;
+
+use super::{counter::Counts, model::LanguageModel, Alphabet};
+
+/// Probability given to the ranks of a text beyond the letters of a model, so
+/// the divergence against it stays finite.
+const MIN_PROBABILITY: f64 = 1e-4;
+
+/// This struct represents how close the letters of a text are to the ones of
+/// a language model.
+///
+/// Since the text is likely cyphered, its letters can not be compared with
+/// the language's ones directly. Instead, both frequency profiles are sorted,
+/// which is kept by any monoalphabetic substitution, and compared rank by
+/// rank, along with their index of coincidence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageGuess<'a> {
+	/// The model the text was compared against.
+	pub model: &'a LanguageModel,
+	/// The divergence, in nats per letter, of the sorted profile of the text
+	/// from the model's one.
+	pub divergence: f64,
+	/// The index of coincidence of the letters of the text.
+	pub coincidence: f64,
+	/// The share of the letters of the text that belong to the model's
+	/// alphabet.
+	pub coverage: f64,
+	/// The overall distance between the text and the model. The lower, the
+	/// better.
+	pub distance: f64,
+	/// The relative confidence (from 0 to 1) of the guess among the rest.
+	pub confidence: f64
+}
+
+/// Compares the letters of the given text with the ones of every given model,
+/// from the most to the least likely language of the text.
+///
+/// The distance to every model is the divergence of the sorted profiles, plus
+/// the relative difference of their indices of coincidence and the share of
+/// the letters of the text the model lacks, so a text is never taken for a
+/// language written in another script.
+pub fn detect_language<'a>(text: &str, models: &'a [LanguageModel]) -> Result<Vec<LanguageGuess<'a>>, Error> {
+	let mut counts = Counts::new();
+	counts.add(text);
+	let letters_len: usize = counts.iter().filter(|(c, _)| c.is_alphabetic()).map(|(_, n)| n).sum();
+	if letters_len == 0 {
+		return Err(Error::EmptyInput);
+	}
+
+	let mut guesses: Vec<_> = models.iter().map(|model| compare(&counts, letters_len, model)).collect();
+	guesses.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+	// the distances are per letter, so they are scaled by the length of the
+	// text, as longer texts leave less room for doubt
+	let best = guesses.first().map_or(0.0, |g| g.distance);
+	let weights: Vec<_> = guesses
+		.iter()
+		.map(|g| match g.distance.is_finite() {
+			true => (-(g.distance - best) * letters_len as f64).exp(),
+			false => 0.0
+		})
+		.collect();
+	let total: f64 = weights.iter().sum();
+	for (g, w) in guesses.iter_mut().zip(weights).filter(|_| total > 0.0) {
+		g.confidence = w / total;
+	}
+
+	Ok(guesses)
+}
+
+/// Compares the given counts of the characters of a text with the given model.
+fn compare<'a>(counts: &Counts, letters_len: usize, model: &'a LanguageModel) -> LanguageGuess<'a> {
+	let alphabet = Alphabet::from_model(model);
+	let mut table = vec![0usize; alphabet.letters().len()];
+	for (c, n) in counts.iter() {
+		let Some(letter) = alphabet.normalize(c) else { continue };
+		if let Some(i) = alphabet.letters().iter().position(|&l| l == letter) {
+			table[i] += n;
+		}
+	}
+	table.sort_by(|a, b| b.cmp(a));
+
+	let mut expected: Vec<f64> = model.letters().iter().map(|(_, f)| *f).collect();
+	expected.sort_by(|a, b| b.total_cmp(a));
+	let expected_total: f64 = expected.iter().sum();
+
+	let len: usize = table.iter().sum();
+	let coverage = len as f64 / letters_len as f64;
+	if len < 2 {
+		return LanguageGuess { model, divergence: 0.0, coincidence: 0.0, coverage, distance: f64::INFINITY, confidence: 0.0 };
+	}
+
+	// the Kullback-Leibler divergence of the sorted profile of the text from
+	// the model's one
+	let divergence: f64 = table
+		.iter()
+		.enumerate()
+		.filter(|(_, &n)| n > 0)
+		.map(|(i, &n)| {
+			let p = n as f64 / len as f64;
+			let q = expected.get(i).map_or(0.0, |f| f / expected_total).max(MIN_PROBABILITY);
+			p * (p / q).ln()
+		})
+		.sum();
+
+	let coincidence = table.iter().map(|&n| (n * n.saturating_sub(1)) as f64).sum::<f64>()
+		/ (len * (len - 1)) as f64;
+	let expected_coincidence: f64 = expected.iter().map(|f| (f / expected_total).powi(2)).sum();
+	let coincidence_diff = (coincidence - expected_coincidence).abs() / expected_coincidence;
+
+	LanguageGuess {
+		model,
+		divergence,
+		coincidence,
+		coverage,
+		distance: divergence + coincidence_diff + (1.0 - coverage),
+		confidence: 0.0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{model::Language, shift::shift_text};
+
+	const ENGLISH: &str = "It is a truth universally acknowledged, that a single man in possession \
+		of a good fortune, must be in want of a wife. However little known the feelings or views \
+		of such a man may be on his first entering a neighbourhood, this truth is so well fixed \
+		in the minds of the surrounding families, that he is considered as the rightful property \
+		of some one or other of their daughters.";
+
+	const SPANISH: &str = "En un lugar de la Mancha, de cuyo nombre no quiero acordarme, no ha \
+		mucho tiempo que vivía un hidalgo de los de lanza en astillero, adarga antigua, rocín \
+		flaco y galgo corredor. Una olla de algo más vaca que carnero, salpicón las más noches, \
+		duelos y quebrantos los sábados, lentejas los viernes, algún palomino de añadidura los \
+		domingos, consumían las tres partes de su hacienda.";
+
+	const GERMAN: &str = "Als Gregor Samsa eines Morgens aus unruhigen Träumen erwachte, fand er \
+		sich in seinem Bett zu einem ungeheueren Ungeziefer verwandelt. Er lag auf seinem \
+		panzerartig harten Rücken und sah, wenn er den Kopf ein wenig hob, seinen gewölbten, \
+		braunen, von bogenförmigen Versteifungen geteilten Bauch, auf dessen Höhe sich die \
+		Bettdecke, zum gänzlichen Niedergleiten bereit, kaum noch erhalten konnte.";
+
+	fn bundled() -> Vec<LanguageModel> {
+		Language::ALL.into_iter().map(LanguageModel::bundled).collect()
+	}
+
+	#[test]
+	fn cyphered_languages_are_detected() {
+		let models = bundled();
+		for (text, name) in [(ENGLISH, "English"), (SPANISH, "Spanish"), (GERMAN, "German")] {
+			let guesses = detect_language(&shift_text(text, 7), &models).unwrap();
+
+			assert_eq!(guesses[0].model.name(), name);
+			assert!(guesses[0].confidence > 0.5);
+			assert!(guesses.windows(2).all(|w| w[0].distance <= w[1].distance));
+		}
+	}
+
+	#[test]
+	fn other_scripts_are_ruled_out() {
+		let greek = LanguageModel::parse("name = Greek\n[letters]\nΑ 12\nΟ 10\nΣ 8\nΞ 1").unwrap();
+		let mut models = bundled();
+		models.push(greek);
+
+		let guesses = detect_language("Άσσος, ξάσα όσο σας.", &models).unwrap();
+
+		assert_eq!(guesses[0].model.name(), "Greek");
+		assert_eq!(guesses[0].coverage, 1.0);
+		assert!(guesses[1..].iter().all(|g| g.distance.is_infinite()));
+		assert!(matches!(detect_language("123", &models), Err(Error::EmptyInput)));
+	}
+}
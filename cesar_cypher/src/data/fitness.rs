@@ -0,0 +1,119 @@
+use super::{model::LanguageModel, ALPHABET_LEN};
+
+/// This struct scores how much a text resembles the language of a model by
+/// adding up the log-probabilities of all of its n-grams.
+///
+/// It uses the longest n-grams available in the model, in the order of
+/// quadgrams, bigrams and single letters.
+#[derive(Debug, Clone)]
+pub struct NgramFitness {
+	n: usize,
+	table: Vec<f64>
+}
+
+impl NgramFitness {
+	/// Builds the fitness function of the given language model.
+	pub fn new(model: &LanguageModel) -> Self {
+		if !model.quadgrams().is_empty() {
+			Self::from_grams(4, model.quadgrams().iter().map(|(g, f)| (g.as_str(), *f)))
+		} else if !model.bigrams().is_empty() {
+			Self::from_grams(2, model.bigrams().iter().map(|(g, f)| (g.as_str(), *f)))
+		} else {
+			let letters: Vec<_> = model
+				.letters()
+				.iter()
+				.map(|(c, f)| (c.to_string(), *f))
+				.collect();
+			Self::from_grams(1, letters.iter().map(|(g, f)| (g.as_str(), *f)))
+		}
+	}
+
+	/// Builds the fitness function over the bigrams of the given language
+	/// model, which is cheaper to compute than the quadgram one, or the one
+	/// given by [`NgramFitness::new`] when the model has no bigrams.
+	pub fn bigrams(model: &LanguageModel) -> Self {
+		match model.bigrams().is_empty() {
+			true => Self::new(model),
+			false => Self::from_grams(2, model.bigrams().iter().map(|(g, f)| (g.as_str(), *f)))
+		}
+	}
+
+	/// Builds the log-probabilities table from the given n-grams and their
+	/// frequencies (as percentages), ignoring the ones with letters outside
+	/// the alphabet.
+	fn from_grams<'a, I>(n: usize, grams: I) -> Self
+		where I: Iterator<Item = (&'a str, f64)>
+	{
+		let grams: Vec<_> = grams
+			.filter_map(|(g, f)| Self::gram_index(g.as_bytes()).map(|i| (i, f / 100.0)))
+			.filter(|(_, p)| *p > 0.0)
+			.collect();
+
+		// unseen n-grams get a probability an order of magnitude below the
+		// least frequent one that was seen
+		let min = grams.iter().map(|(_, p)| *p).fold(1.0, f64::min);
+		let mut table = vec![(min / 10.0).log10(); ALPHABET_LEN.pow(n as u32)];
+		for (i, p) in grams {
+			table[i] = p.log10();
+		}
+
+		Self { n, table }
+	}
+
+	/// Computes the position inside the table of the given n-gram.
+	fn gram_index(gram: &[u8]) -> Option<usize> {
+		gram.iter().try_fold(0, |acc, &c| match c {
+			b'A'..=b'Z' => Some(acc * ALPHABET_LEN + (c - b'A') as usize),
+			_ => None
+		})
+	}
+
+	/// Scores the given sequence of letters, expressed as their position
+	/// inside the alphabet. The higher the score, the better.
+	pub fn score(&self, letters: &[u8]) -> f64 {
+		letters
+			.windows(self.n)
+			.map(|w| {
+				let i = w.iter().fold(0, |acc, &c| acc * ALPHABET_LEN + c as usize);
+				self.table[i]
+			})
+			.sum()
+	}
+
+	/// Scores the given sequence of letters, some of which may be unknown,
+	/// skipping the n-grams that hold any of them. Gives back the score along
+	/// with the amount of n-grams it was computed over.
+	pub fn score_known(&self, letters: &[Option<u8>]) -> (f64, usize) {
+		letters
+			.windows(self.n)
+			.filter_map(|w| w.iter().try_fold(0, |acc, c| c.map(|c| acc * ALPHABET_LEN + c as usize)))
+			.fold((0.0, 0), |(score, count), i| (score + self.table[i], count + 1))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::letter_indices;
+
+	#[test]
+	fn english_scores_better_than_gibberish() {
+		let fitness = NgramFitness::new(&LanguageModel::default());
+		let english = letter_indices("THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG");
+		let gibberish = letter_indices("QXZ JKQWV PZXQF KJQ XZVWQ JQKX QZJ XQWKZ JVQ");
+
+		assert_eq!(fitness.n, 4);
+		assert!(fitness.score(&english) > fitness.score(&gibberish));
+	}
+
+	#[test]
+	fn unknown_letters_are_skipped() {
+		let fitness = NgramFitness::new(&LanguageModel::default());
+		let known: Vec<_> = letter_indices("THEQUICK").into_iter().map(Some).collect();
+		let mut partial = known.clone();
+		partial[4] = None;
+
+		assert_eq!(fitness.score_known(&known), (fitness.score(&letter_indices("THEQUICK")), 5));
+		assert_eq!(fitness.score_known(&partial).1, 1);
+	}
+}
@@ -0,0 +1,393 @@
+use rand::Rng;
+
+use crate::error::Error;
+
+use super::{fitness::NgramFitness, match_case, Analizer, ALPHABET_LEN};
+
+/// Number of random reassignments tried by each annealing run, for every
+/// symbol of the text.
+const ANNEALING_STEPS: usize = 8_000;
+/// Initial temperature of the annealing, in log-probability units.
+const ANNEALING_TEMPERATURE: f64 = 4.0;
+/// Amount of letters around a changed one whose n-grams are rescored, as the
+/// fitness uses quadgrams at most.
+const CONTEXT: usize = 3;
+
+/// This struct represents the best solution found by the homophonic cracker,
+/// where every symbol of the text stands for a single plain letter, but a
+/// plain letter may be written with several symbols.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HomophonicSolution {
+	/// The distinct symbols of the text, in the order they first appear.
+	pub symbols: Vec<String>,
+	/// The plain letter of every symbol, at its same position.
+	pub letters: Vec<char>,
+	/// The n-gram log-probability of the plaintext, along with the one of
+	/// picking its symbols among the homophones of their letters. The higher,
+	/// the better.
+	pub score: f64,
+	/// The text resulting from replacing every symbol by its plain letter.
+	pub text: String
+}
+
+impl HomophonicSolution {
+	/// Retrieves every plain letter of the solution along with the symbols that
+	/// stand for it, in alphabetical order.
+	pub fn homophones(&self) -> Vec<(char, Vec<&str>)> {
+		('A'..='Z')
+			.map(|p| {
+				let symbols = self.symbols
+					.iter()
+					.zip(self.letters.iter())
+					.filter(|(_, &l)| l == p)
+					.map(|(s, _)| s.as_str())
+					.collect();
+				(p, symbols)
+			})
+			.filter(|(_, symbols): &(char, Vec<&str>)| !symbols.is_empty())
+			.collect()
+	}
+
+	/// Writes the key as every plain letter followed by its symbols, such as
+	/// `A:12,40 B:07`.
+	pub fn key(&self) -> String {
+		self.homophones()
+			.iter()
+			.map(|(p, symbols)| format!("{p}:{}", symbols.join(",")))
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+}
+
+impl Analizer {
+	/// Searches the homophonic substitution key that best decrypts the given
+	/// text, whose symbol alphabet may be larger than the language's one.
+	///
+	/// When every word of the text is a number, each one of them is a symbol,
+	/// otherwise every character but the whitespace is. The key is searched
+	/// by simulated annealing over the n-gram fitness of the language, moving
+	/// a single symbol to another letter at a time, followed by hill-climbing,
+	/// and the search is repeated as many times as specified from random keys.
+	///
+	/// Every restart tries `ANNEALING_STEPS` moves for each distinct symbol,
+	/// and every move rescores the n-grams around each occurrence of the
+	/// symbol, so a restart costs about the amount of symbols times the length
+	/// of the text.
+	///
+	/// When the symbols are characters, the letters written back over the text
+	/// take the case of the symbol they replace.
+	pub fn crack_homophonic(&self, text: &str, restarts: usize) -> Result<HomophonicSolution, Error> {
+		self.crack_homophonic_with(text, restarts, &mut rand::thread_rng())
+	}
+
+	/// Same as [`Analizer::crack_homophonic`], but drawing the random keys
+	/// from the given generator.
+	pub fn crack_homophonic_with<R: Rng + ?Sized>(
+		&self,
+		text: &str,
+		restarts: usize,
+		rng: &mut R
+	) -> Result<HomophonicSolution, Error>
+	{
+		let (symbols, sequence, numbered) = parse_symbols(text);
+		if sequence.is_empty() {
+			return Err(Error::EmptyInput);
+		}
+
+		let fitness = NgramFitness::new(&self.model);
+		let cyphered = SymbolText::new(symbols.len(), sequence);
+
+		let mut best = (Vec::new(), f64::NEG_INFINITY);
+		for _ in 0..restarts.max(1) {
+			let key = (0..symbols.len()).map(|_| rng.gen_range(0..ALPHABET_LEN as u8)).collect();
+			let key = anneal(&mut Search::new(&fitness, &cyphered, key), rng);
+			let mut search = Search::new(&fitness, &cyphered, key);
+			let score = hill_climb(&mut search);
+			if score > best.1 {
+				best = (search.key, score);
+			}
+		}
+
+		let (key, score) = best;
+		let letters: Vec<char> = key.iter().map(|&p| (b'A' + p) as char).collect();
+		// the symbols that are characters are written back over the text, so
+		// its layout is kept
+		let mut plain = cyphered.sequence.iter().map(|&s| letters[s]);
+		let text = match numbered {
+			true => plain.collect(),
+			false => text
+				.chars()
+				.map(|c| match c.is_whitespace() {
+					true => c,
+					false => plain.next().map_or(c, |p| match_case(p, c))
+				})
+				.collect()
+		};
+
+		Ok(HomophonicSolution { symbols, letters, score, text })
+	}
+}
+
+/// Splits the given text into its distinct symbols, in the order they first
+/// appear, and the position of each symbol of the text among them, telling
+/// whether the symbols are numbers.
+fn parse_symbols(text: &str) -> (Vec<String>, Vec<usize>, bool) {
+	let words: Vec<&str> = text.split_whitespace().collect();
+	let numbered = !words.is_empty() && words.iter().all(|w| w.chars().all(|c| c.is_ascii_digit()));
+	let tokens: Vec<String> = match numbered {
+		true => words.iter().map(|w| w.to_string()).collect(),
+		false => text.chars().filter(|c| !c.is_whitespace()).map(String::from).collect()
+	};
+
+	let mut symbols: Vec<String> = Vec::new();
+	let sequence = tokens
+		.into_iter()
+		.map(|t| match symbols.iter().position(|s| *s == t) {
+			Some(i) => i,
+			None => {
+				symbols.push(t);
+				symbols.len() - 1
+			}
+		})
+		.collect();
+
+	(symbols, sequence, numbered)
+}
+
+/// This struct represents the cyphered text as the sequence of its symbols,
+/// along with what the search needs to know about every one of them.
+struct SymbolText {
+	/// The position of each symbol of the text among the distinct ones.
+	sequence: Vec<usize>,
+	/// The stretches of the text whose n-grams change when the letter of a
+	/// symbol does, merged so no n-gram is scored twice.
+	spans: Vec<Vec<(usize, usize)>>,
+	/// The amount of times every symbol appears.
+	occurrences: Vec<usize>
+}
+
+impl SymbolText {
+	fn new(symbols: usize, sequence: Vec<usize>) -> Self {
+		let mut spans: Vec<Vec<(usize, usize)>> = vec![Vec::new(); symbols];
+		let mut occurrences = vec![0; symbols];
+		for (i, &s) in sequence.iter().enumerate() {
+			let (start, end) = (i.saturating_sub(CONTEXT), (i + CONTEXT + 1).min(sequence.len()));
+			match spans[s].last_mut() {
+				Some(last) if start < last.1 => last.1 = end,
+				_ => spans[s].push((start, end))
+			}
+			occurrences[s] += 1;
+		}
+
+		Self { sequence, spans, occurrences }
+	}
+}
+
+/// This struct represents a homophonic key under search, along with the
+/// plaintext it gives and the counts its score is updated from.
+///
+/// The score is the n-gram fitness of the plaintext plus the log-probability
+/// of picking the symbols of the text among the homophones of their letters,
+/// which keeps the search from writing the whole text with a few common
+/// letters.
+struct Search<'a> {
+	fitness: &'a NgramFitness,
+	text: &'a SymbolText,
+	key: Vec<u8>,
+	plain: Vec<u8>,
+	/// The amount of letters of the plaintext of every letter of the alphabet.
+	counts: [usize; ALPHABET_LEN],
+	/// The amount of symbols that stand for every letter of the alphabet.
+	homophones: [usize; ALPHABET_LEN]
+}
+
+impl<'a> Search<'a> {
+	fn new(fitness: &'a NgramFitness, text: &'a SymbolText, key: Vec<u8>) -> Self {
+		let mut counts = [0; ALPHABET_LEN];
+		let mut homophones = [0; ALPHABET_LEN];
+		for (&p, &n) in key.iter().zip(text.occurrences.iter()) {
+			counts[p as usize] += n;
+			homophones[p as usize] += 1;
+		}
+		let plain = text.sequence.iter().map(|&s| key[s]).collect();
+
+		Self { fitness, text, key, plain, counts, homophones }
+	}
+
+	/// Scores the whole key. The higher, the better.
+	fn score(&self) -> f64 {
+		let choice: f64 = (0..ALPHABET_LEN).map(|p| self.choice(p)).sum();
+		self.fitness.score(&self.plain) + choice
+	}
+
+	/// Computes the log-probability of picking the symbols of the given letter
+	/// among its homophones, all of them being equally likely.
+	fn choice(&self, p: usize) -> f64 {
+		match self.homophones[p] {
+			0 => 0.0,
+			k => -(self.counts[p] as f64) * (k as f64).log10()
+		}
+	}
+
+	/// Changes the letter of the given symbol, returning the change of the
+	/// score of the key.
+	fn reassign(&mut self, symbol: usize, letter: u8) -> f64 {
+		let (old, new) = (self.key[symbol] as usize, letter as usize);
+		let spans = &self.text.spans[symbol];
+		let score_spans = |plain: &[u8]| -> f64 {
+			spans.iter().map(|&(start, end)| self.fitness.score(&plain[start..end])).sum()
+		};
+
+		let before = score_spans(&self.plain) + self.choice(old) + self.choice(new);
+		for &(start, end) in spans {
+			for i in start..end {
+				if self.text.sequence[i] == symbol { self.plain[i] = letter; }
+			}
+		}
+		self.key[symbol] = letter;
+		let n = self.text.occurrences[symbol];
+		self.counts[old] -= n;
+		self.homophones[old] -= 1;
+		self.counts[new] += n;
+		self.homophones[new] += 1;
+
+		score_spans(&self.plain) + self.choice(old) + self.choice(new) - before
+	}
+}
+
+/// Explores the keys around the given one by moving random symbols to random
+/// letters, accepting worse keys with a probability that decreases as the
+/// search cools down, and returns the best key found along the way.
+fn anneal<R: Rng + ?Sized>(search: &mut Search, rng: &mut R) -> Vec<u8> {
+	let mut current = search.score();
+	let mut best = (search.key.clone(), current);
+
+	let steps = ANNEALING_STEPS * search.key.len();
+	for step in 0..steps {
+		let temperature = ANNEALING_TEMPERATURE * (1.0 - step as f64 / steps as f64);
+		let symbol = rng.gen_range(0..search.key.len());
+		let (old, letter) = (search.key[symbol], rng.gen_range(0..ALPHABET_LEN as u8));
+		if letter == old { continue; }
+
+		let delta = search.reassign(symbol, letter);
+		if delta >= 0.0 || rng.gen::<f64>() < (delta / temperature).exp() {
+			current += delta;
+			if current > best.1 { best = (search.key.clone(), current); }
+		} else {
+			search.reassign(symbol, old);
+		}
+	}
+
+	best.0
+}
+
+/// Improves the key by moving its symbols to other letters as long as any
+/// move increases its score, returning the final score.
+fn hill_climb(search: &mut Search) -> f64 {
+	let mut improved = true;
+	while improved {
+		improved = false;
+		for symbol in 0..search.key.len() {
+			for letter in 0..ALPHABET_LEN as u8 {
+				let old = search.key[symbol];
+				if letter == old { continue; }
+
+				match search.reassign(symbol, letter) > 0.0 {
+					true => improved = true,
+					false => {
+						search.reassign(symbol, old);
+					}
+				}
+			}
+		}
+	}
+
+	search.score()
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{rngs::StdRng, SeedableRng};
+
+	use super::*;
+	use super::super::{letter_indices, model::LanguageModel};
+
+	const PLAIN: &str = "IT IS A TRUTH UNIVERSALLY ACKNOWLEDGED THAT A SINGLE MAN IN POSSESSION OF A GOOD \
+		FORTUNE MUST BE IN WANT OF A WIFE HOWEVER LITTLE KNOWN THE FEELINGS OR VIEWS OF SUCH A MAN MAY BE \
+		ON HIS FIRST ENTERING A NEIGHBOURHOOD THIS TRUTH IS SO WELL FIXED IN THE MINDS OF THE SURROUNDING \
+		FAMILIES THAT HE IS CONSIDERED THE RIGHTFUL PROPERTY OF SOME ONE OR OTHER OF THEIR DAUGHTERS MY \
+		DEAR MR BENNET SAID HIS LADY TO HIM ONE DAY HAVE YOU HEARD THAT NETHERFIELD PARK IS LET AT LAST \
+		MR BENNET REPLIED THAT HE HAD NOT BUT IT IS RETURNED SHE FOR MRS LONG HAS JUST BEEN HERE AND SHE \
+		TOLD ME ALL ABOUT IT MR BENNET MADE NO ANSWER DO YOU NOT WANT TO KNOW WHO HAS TAKEN IT CRIED HIS \
+		WIFE IMPATIENTLY YOU WANT TO TELL ME AND I HAVE NO OBJECTION TO HEARING IT";
+
+	/// Cyphers the letters of the given text with numbered symbols, giving the
+	/// most frequent letters several of them, used in turns.
+	fn homophonic_encrypt(text: &str) -> String {
+		let homophones = |p: u8| match p + b'A' {
+			b'E' | b'T' => 4,
+			b'A' | b'O' | b'I' | b'N' | b'S' | b'H' | b'R' => 3,
+			_ => 1
+		};
+		let first: Vec<usize> = (0..ALPHABET_LEN as u8)
+			.scan(0, |next, p| {
+				let first = *next;
+				*next += homophones(p);
+				Some(first)
+			})
+			.collect();
+
+		let mut turns = [0; ALPHABET_LEN];
+		letter_indices(text)
+			.iter()
+			.map(|&p| {
+				let turn = turns[p as usize] % homophones(p);
+				turns[p as usize] += 1;
+				// the symbols are scattered so they do not follow the alphabet
+				format!("{:02}", (first[p as usize] + turn) * 37 % 97)
+			})
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+
+	#[test]
+	fn symbols_are_parsed() {
+		let (symbols, sequence, numbered) = parse_symbols("12 7 12 40");
+		assert_eq!((symbols, sequence, numbered), (vec!["12".to_owned(), "7".to_owned(), "40".to_owned()], vec![0, 1, 0, 2], true));
+
+		let (symbols, sequence, numbered) = parse_symbols("a%a 7");
+		assert_eq!((symbols, sequence, numbered), (vec!["a".to_owned(), "%".to_owned(), "7".to_owned()], vec![0, 1, 0, 2], false));
+	}
+
+	#[test]
+	fn homophonic_is_cracked() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = homophonic_encrypt(PLAIN);
+
+		let solution = analizer.crack_homophonic_with(&cyphered, 1, &mut StdRng::seed_from_u64(0)).unwrap();
+		let plain: Vec<char> = PLAIN.chars().filter(char::is_ascii_alphabetic).collect();
+
+		// a symbol seen a single time, such as the one of the X of "FIXED",
+		// cannot be told apart from the other rare letters, but every other
+		// one has to be recovered
+		let words: Vec<&str> = cyphered.split_whitespace().collect();
+		for (symbol, letter) in solution.symbols.iter().zip(solution.letters.iter()) {
+			let position = words.iter().position(|w| w == symbol).unwrap();
+			if words.iter().filter(|w| *w == symbol).count() > 1 {
+				assert_eq!(*letter, plain[position], "symbol {symbol}: {}", solution.text);
+			}
+		}
+		assert!(solution.symbols.len() > ALPHABET_LEN);
+		assert_eq!(solution.homophones().iter().find(|(p, _)| *p == 'E').unwrap().1.len(), 4);
+	}
+
+	#[test]
+	fn case_of_the_symbols_is_kept() {
+		let analizer = Analizer::new(LanguageModel::default());
+
+		let solution = analizer.crack_homophonic_with("Xq xQ%", 1, &mut StdRng::seed_from_u64(0)).unwrap();
+		let case: Vec<_> = solution.text.chars().map(|c| (c.is_whitespace(), c.is_lowercase())).collect();
+
+		assert_eq!(case, [(false, false), (false, true), (true, false), (false, true), (false, false), (false, false)]);
+	}
+}
@@ -0,0 +1,277 @@
+use std::fmt::Display;
+
+use crate::error::Error;
+
+use super::{letter_counts, letter_indices, Analizer, ALPHABET_LEN};
+
+/// Converts the distances of the cypher types into natural log-likelihoods.
+const CONFIDENCE_SCALE: f64 = 8.0;
+
+/// Minimum amount of letters every column of a period must have for its
+/// index of coincidence to be taken into account.
+const MIN_COLUMN_LEN: usize = 12;
+
+/// Scaled index of coincidence the columns of a period must reach to be taken
+/// as the period of the text.
+const PERIOD_COINCIDENCE: f64 = 0.75;
+
+/// The kinds of cypher a text can be told apart by its statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherType {
+	/// The letters of the plain text are reordered, but kept as they are.
+	Transposition,
+	/// Every letter is replaced by a same letter along the whole text.
+	Monoalphabetic,
+	/// The letters are replaced by several alphabets, used in turns.
+	Polyalphabetic,
+	/// The text is not cyphered, but written with another set of symbols.
+	Encoding
+}
+
+impl Display for CipherType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			Self::Transposition => "transposition",
+			Self::Monoalphabetic => "monoalphabetic",
+			Self::Polyalphabetic => "polyalphabetic",
+			Self::Encoding => "encoding"
+		};
+
+		write!(f, "{name}")
+	}
+}
+
+/// This struct represents the measurable features of a text the cypher that
+/// produced it is told apart by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CipherFeatures {
+	/// The share of the symbols of the text, whitespace aside, that are
+	/// letters.
+	pub letter_share: f64,
+	/// The encoding whose character set the text is written with, if any.
+	pub encoding: Option<&'static str>,
+	/// The total variation distance (from 0 to 1) of the letter frequencies
+	/// of the text from the language's ones, letter by letter.
+	pub frequency_distance: f64,
+	/// The same distance, with both frequency profiles sorted, so it is kept
+	/// by any monoalphabetic substitution.
+	pub profile_distance: f64,
+	/// The index of coincidence of the text, scaled from a random text (0) to
+	/// the language (1).
+	pub coincidence: f64,
+	/// The most likely period of the text, with the average index of
+	/// coincidence of its columns scaled like the text's one.
+	pub period: usize,
+	pub period_coincidence: f64
+}
+
+/// This struct represents a possible type of the cypher of a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CipherGuess {
+	pub cipher: CipherType,
+	/// How far the features of the text are from the ones expected for the
+	/// type. The lower, the better.
+	pub distance: f64,
+	/// The relative confidence (from 0 to 1) of the guess among the rest.
+	pub confidence: f64
+}
+
+/// This struct represents the identification of the cypher of a text, with
+/// the guesses ranked from the most to the least likely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identification {
+	pub features: CipherFeatures,
+	pub guesses: Vec<CipherGuess>
+}
+
+impl Analizer {
+	/// Identifies the type of cypher that most likely produced the given text.
+	///
+	/// An encoding is told by the character set of the text. Otherwise, a
+	/// transposition keeps the letter frequencies of the language, while a
+	/// monoalphabetic substitution only keeps their profile and index of
+	/// coincidence, and a polyalphabetic one flattens the latter, which is
+	/// only recovered by splitting the text by its period.
+	pub fn identify_cipher(&self, text: &str, max_key_len: usize) -> Result<Identification, Error> {
+		let symbols: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+		if symbols.is_empty() {
+			return Err(Error::EmptyInput);
+		}
+
+		let encoding = detect_encoding(text);
+		let letters = letter_indices(text);
+		let letter_share = letters.len() as f64 / symbols.len() as f64;
+		if letters.len() < 2 && encoding.is_none() {
+			return Err(Error::EmptyInput);
+		}
+
+		let expected = self.letters_table();
+		let expected_ic: f64 = expected.iter().map(|p| p * p).sum();
+		let random_ic = 1.0 / ALPHABET_LEN as f64;
+		let scale_ic = |ic: f64| (ic - random_ic) / (expected_ic - random_ic);
+
+		let counts = letter_counts(text);
+		let total = letters.len().max(1) as f64;
+		let observed: Vec<f64> = counts.iter().map(|&c| c as f64 / total).collect();
+		let frequency_distance = total_variation(&observed, &expected);
+
+		let mut sorted_observed = observed.clone();
+		let mut sorted_expected = expected.to_vec();
+		sorted_observed.sort_by(|a, b| b.total_cmp(a));
+		sorted_expected.sort_by(|a, b| b.total_cmp(a));
+		let profile_distance = total_variation(&sorted_observed, &sorted_expected);
+
+		// the multiples of the period keep its coincidence, so the shortest
+		// period whose columns look like the language is taken, or else the
+		// one closest to it
+		let max_period = max_key_len.min(letters.len() / MIN_COLUMN_LEN).max(1);
+		let mut key_lengths = self.rank_key_lengths(&letters, max_period);
+		key_lengths.sort_by_key(|k| k.length);
+		let (period, period_coincidence) = key_lengths
+			.iter()
+			.find(|k| scale_ic(k.coincidence) >= PERIOD_COINCIDENCE)
+			.or_else(|| key_lengths.iter().max_by(|a, b| a.coincidence.total_cmp(&b.coincidence)))
+			.map_or((1, 0.0), |k| (k.length, scale_ic(k.coincidence)));
+
+		let features = CipherFeatures {
+			letter_share,
+			encoding,
+			frequency_distance,
+			profile_distance,
+			coincidence: scale_ic(super::vigenere::index_of_coincidence(&letters)),
+			period,
+			period_coincidence
+		};
+
+		let mut guesses: Vec<_> = [
+			CipherType::Transposition,
+			CipherType::Monoalphabetic,
+			CipherType::Polyalphabetic,
+			CipherType::Encoding
+		]
+			.into_iter()
+			.map(|cipher| CipherGuess { cipher, distance: distance(cipher, &features), confidence: 0.0 })
+			.collect();
+		guesses.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+		let best = guesses[0].distance;
+		let weights: Vec<_> = guesses
+			.iter()
+			.map(|g| (-(g.distance - best) * CONFIDENCE_SCALE).exp())
+			.collect();
+		let total: f64 = weights.iter().sum();
+		for (g, w) in guesses.iter_mut().zip(weights) {
+			g.confidence = w / total;
+		}
+
+		Ok(Identification { features, guesses })
+	}
+}
+
+/// Measures how far the given features are from the ones expected for the
+/// given type of cypher.
+fn distance(cipher: CipherType, f: &CipherFeatures) -> f64 {
+	// the cyphers over letters expect a text made of letters
+	let not_letters = 2.0 * (1.0 - f.letter_share);
+	match cipher {
+		CipherType::Encoding => match f.encoding {
+			Some(_) => 0.0,
+			None => 1.0 + 2.0 * f.letter_share
+		},
+		CipherType::Transposition => {
+			not_letters + 2.0 * f.frequency_distance + (1.0 - f.coincidence).abs()
+		},
+		CipherType::Monoalphabetic => {
+			// the frequencies matching letter by letter point to a
+			// transposition instead
+			let unchanged = 3.0 * (0.3 - f.frequency_distance).max(0.0);
+			not_letters + 2.0 * f.profile_distance + (1.0 - f.coincidence).abs() + unchanged
+		},
+		CipherType::Polyalphabetic => {
+			// a period of one is a monoalphabetic cypher
+			let aperiodic = match f.period { 1 => 0.5, _ => 0.0 };
+			not_letters + f.coincidence.max(0.0) + (1.0 - f.period_coincidence).abs() + aperiodic
+		}
+	}
+}
+
+/// Measures the total variation distance between two distributions.
+fn total_variation(p: &[f64], q: &[f64]) -> f64 {
+	p.iter().zip(q.iter()).map(|(a, b)| (a - b).abs()).sum::<f64>() / 2.0
+}
+
+/// Determines whether the given text is written with the character set of a
+/// common encoding, whitespace aside.
+fn detect_encoding(text: &str) -> Option<&'static str> {
+	let symbols: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+	let only = |set: &dyn Fn(char) -> bool| !symbols.is_empty() && symbols.chars().all(set);
+
+	if only(&|c| matches!(c, '.' | '-' | '/' | '|')) {
+		return Some("morse");
+	}
+	if only(&|c| matches!(c, '0' | '1')) && groups_of(&symbols, 8) {
+		return Some("binary");
+	}
+	if only(&|c| c.is_ascii_digit() || matches!(c, ',' | '-')) {
+		return Some("decimal");
+	}
+	if only(&|c| c.is_ascii_hexdigit()) && groups_of(&symbols, 2) {
+		return Some("hexadecimal");
+	}
+
+	// a text of plain letters can be mistaken for base64, so it also has to
+	// mix both cases and hold a digit or a symbol of its own
+	let padding = symbols.trim_end_matches('=');
+	let base64 = only(&|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '='))
+		&& groups_of(&symbols, 4)
+		&& symbols.len() - padding.len() <= 2
+		&& !padding.contains('=')
+		&& padding.chars().any(|c| c.is_ascii_lowercase())
+		&& padding.chars().any(|c| c.is_ascii_uppercase())
+		&& padding.chars().any(|c| c.is_ascii_digit() || matches!(c, '+' | '/'));
+	base64.then_some("base64")
+}
+
+/// Determines whether the given symbols split into whole groups of the given
+/// size, such as the bits of every byte.
+fn groups_of(symbols: &str, size: usize) -> bool {
+	symbols.as_bytes().chunks_exact(size).remainder().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{model::LanguageModel, shift::shift_text};
+	use crate::cipher::{Cipher, Vigenere};
+
+	const PLAIN: &str = "It is a truth universally acknowledged, that a single man in possession \
+		of a good fortune, must be in want of a wife. However little known the feelings or views \
+		of such a man may be on his first entering a neighbourhood, this truth is so well fixed \
+		in the minds of the surrounding families, that he is considered as the rightful property \
+		of some one or other of their daughters.";
+
+	fn identify(text: &str) -> CipherType {
+		let analizer = Analizer::new(LanguageModel::default());
+		analizer.identify_cipher(text, 12).unwrap().guesses[0].cipher
+	}
+
+	#[test]
+	fn letter_cyphers_are_identified() {
+		let reversed: String = PLAIN.chars().rev().collect();
+		let vigenere = Vigenere::new("LEMON").unwrap().encrypt(PLAIN);
+
+		assert_eq!(identify(&reversed), CipherType::Transposition);
+		assert_eq!(identify(&shift_text(PLAIN, 11)), CipherType::Monoalphabetic);
+		assert_eq!(identify(&vigenere), CipherType::Polyalphabetic);
+	}
+
+	#[test]
+	fn encodings_are_identified() {
+		assert_eq!(detect_encoding("SGVsbG8sIHdvcmxkIQ=="), Some("base64"));
+		assert_eq!(detect_encoding("48 65 6c 6c 6f"), Some("hexadecimal"));
+		assert_eq!(detect_encoding("01001000 01101001"), Some("binary"));
+		assert_eq!(detect_encoding(".... . .-.. .-.. --- / .-- --- .-. .-.. -.."), Some("morse"));
+		assert_eq!(detect_encoding("WKHTXLFNEURZQIRA"), None);
+		assert_eq!(identify("SGVsbG8sIHdvcmxkIQ=="), CipherType::Encoding);
+	}
+}
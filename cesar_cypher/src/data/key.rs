@@ -0,0 +1,259 @@
+use std::{fmt::{self, Display}, str::FromStr};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::error::Error;
+
+use super::{match_case, ALPHABET_LEN};
+
+/// Position held by the cyphered letters whose plain letter is unknown.
+const UNKNOWN: u8 = u8::MAX;
+
+/// This struct represents a monoalphabetic substitution key, which maps
+/// every cyphered letter to its plain counterpart.
+///
+/// A key may be partial, leaving the plain letter of some cyphered ones
+/// unknown, but it never maps two cyphered letters to a same plain one. It
+/// is written as the one-line plain alphabet, with a `-` for every unknown
+/// letter, which is also the format it is parsed from, so a recovered key can
+/// be saved and applied to other texts later on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstitutionKey {
+	/// The plain letter of each cyphered one, both expressed as their
+	/// position inside the alphabet, or `UNKNOWN`.
+	table: [u8; ALPHABET_LEN]
+}
+
+impl SubstitutionKey {
+	/// Creates the key that leaves every letter untouched.
+	pub fn identity() -> Self {
+		let mut table = [0; ALPHABET_LEN];
+		for (i, p) in table.iter_mut().enumerate() {
+			*p = i as u8;
+		}
+
+		Self { table }
+	}
+
+	/// Creates the partial key that knows no letter at all.
+	pub fn unknown() -> Self {
+		Self { table: [UNKNOWN; ALPHABET_LEN] }
+	}
+
+	/// Creates a key from the plain letter of each cyphered one, ensuring
+	/// that no plain letter is repeated.
+	pub fn from_table(table: [u8; ALPHABET_LEN]) -> Option<Self> {
+		Self::partial(table.map(Some)).filter(Self::is_complete)
+	}
+
+	/// Creates a partial key from the plain letter of each cyphered one, if
+	/// known, ensuring that no plain letter is repeated.
+	pub fn partial(table: [Option<u8>; ALPHABET_LEN]) -> Option<Self> {
+		let mut seen = [false; ALPHABET_LEN];
+		for &p in table.iter().flatten() {
+			if p as usize >= ALPHABET_LEN || seen[p as usize] { return None; }
+			seen[p as usize] = true;
+		}
+
+		Some(Self { table: table.map(|p| p.unwrap_or(UNKNOWN)) })
+	}
+
+	/// Creates a partial key from the given pairs of cyphered and plain
+	/// letters, where an unknown plain letter is given as `?`.
+	pub fn from_associations(associations: &[(char, char)]) -> Result<Self, Error> {
+		let index = |c: char| match c.to_ascii_uppercase() {
+			u @ 'A'..='Z' => Ok(u as u8 - b'A'),
+			_ => Err(Error::UnsupportedSymbol(c))
+		};
+
+		let mut table = [None; ALPHABET_LEN];
+		for &(c, p) in associations.iter().filter(|(_, p)| *p != '?') {
+			let (c, p) = (index(c)?, index(p)?);
+			if table[c as usize].is_some_and(|q| q != p) {
+				return Err(Error::InvalidKey(format!("{} is mapped to several letters", (c + b'A') as char)));
+			}
+			table[c as usize] = Some(p);
+		}
+
+		Self::partial(table).ok_or_else(|| Error::InvalidKey("several letters share a same plain letter".to_owned()))
+	}
+
+	/// Creates a randomly shuffled key.
+	pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+		let mut key = Self::identity();
+		key.table.shuffle(rng);
+
+		key
+	}
+
+	/// Determines whether the plain letter of every cyphered one is known, so
+	/// the key is a bijection of the alphabet.
+	pub fn is_complete(&self) -> bool {
+		self.table.iter().all(|&p| p != UNKNOWN)
+	}
+
+	/// Retrieves the plain letter of the given cyphered one, both expressed as
+	/// their position inside the alphabet, if known.
+	pub fn get(&self, c: u8) -> Option<u8> {
+		self.table.get(c as usize).copied().filter(|&p| p != UNKNOWN)
+	}
+
+	/// Retrieves the pairs of cyphered and plain letters of the key, leaving
+	/// the unknown ones out.
+	pub fn associations(&self) -> Vec<(char, char)> {
+		(0..ALPHABET_LEN as u8)
+			.filter_map(|c| self.get(c).map(|p| ((c + b'A') as char, (p + b'A') as char)))
+			.collect()
+	}
+
+	/// Retrieves the pair of every distinct letter of the given text and its
+	/// plain letter, or `?` when it is unknown, in alphabetical order.
+	pub fn text_associations(&self, text: &str) -> Vec<(char, char)> {
+		let mut seen = [false; ALPHABET_LEN];
+		for c in text.chars().filter(char::is_ascii_alphabetic) {
+			seen[(c.to_ascii_uppercase() as u8 - b'A') as usize] = true;
+		}
+
+		(0..ALPHABET_LEN as u8)
+			.filter(|&c| seen[c as usize])
+			.map(|c| ((c + b'A') as char, self.get(c).map_or('?', |p| (p + b'A') as char)))
+			.collect()
+	}
+
+	/// Exchanges the plain letters of two cyphered ones.
+	pub fn swap(&mut self, a: usize, b: usize) {
+		self.table.swap(a, b);
+	}
+
+	/// Maps the given cyphered letter to the given plain one, or leaves it
+	/// unknown. The cyphered letter that held the plain one before takes the
+	/// former plain letter of the given one, so no plain letter is repeated.
+	pub fn set(&mut self, c: u8, p: Option<u8>) {
+		let p = p.unwrap_or(UNKNOWN);
+		if let Some(o) = self.table.iter().position(|&q| q == p && p != UNKNOWN) {
+			self.table[o] = self.table[c as usize];
+		}
+		self.table[c as usize] = p;
+	}
+
+	/// Builds the key that undoes this one, turning the plain letters back
+	/// into the cyphered ones.
+	pub fn inverse(&self) -> Self {
+		let mut table = [UNKNOWN; ALPHABET_LEN];
+		for (c, &p) in self.table.iter().enumerate().filter(|(_, &p)| p != UNKNOWN) {
+			table[p as usize] = c as u8;
+		}
+
+		Self { table }
+	}
+
+	/// Builds the key that applies this key first and then the given one. A
+	/// letter is only known when it is known by both keys.
+	pub fn compose(&self, next: &Self) -> Self {
+		Self {
+			table: self.table.map(|p| match p {
+				UNKNOWN => UNKNOWN,
+				_ => next.table[p as usize]
+			})
+		}
+	}
+
+	/// Decrypts the given sequence of letters of a complete key, expressed as
+	/// their position inside the alphabet, into the given buffer.
+	pub(crate) fn decrypt_indices(&self, letters: &[u8], out: &mut Vec<u8>) {
+		out.clear();
+		out.extend(letters.iter().map(|&c| self.table[c as usize]));
+	}
+
+	/// Applies the key over the given text, keeping the case of its letters
+	/// and leaving untouched the ones the key does not know, along with the
+	/// rest of the characters.
+	pub fn apply(&self, text: &str) -> String {
+		text
+			.chars()
+			.map(|c| match c.is_ascii_alphabetic() {
+				true => self
+					.get(c.to_ascii_uppercase() as u8 - b'A')
+					.map_or(c, |p| match_case((p + b'A') as char, c)),
+				false => c
+			})
+			.collect()
+	}
+}
+
+impl Display for SubstitutionKey {
+	/// Formats the key as the plain alphabet, where each position holds the
+	/// plain letter of the cyphered letter at that same position, or `-` when
+	/// it is unknown.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for &p in self.table.iter() {
+			match p {
+				UNKNOWN => write!(f, "-")?,
+				_ => write!(f, "{}", (p + b'A') as char)?
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl FromStr for SubstitutionKey {
+	type Err = Error;
+
+	/// Parses a key written as its plain alphabet, where the unknown letters
+	/// are given as either `-` or `?`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let symbols: Vec<char> = s.trim().chars().collect();
+		if symbols.len() != ALPHABET_LEN {
+			return Err(Error::InvalidKey(format!(
+				"a substitution key is the plain alphabet, {ALPHABET_LEN} letters long, not {}",
+				symbols.len()
+			)));
+		}
+
+		let associations: Vec<_> = symbols
+			.into_iter()
+			.enumerate()
+			.map(|(c, p)| ((c as u8 + b'A') as char, if p == '-' { '?' } else { p }))
+			.collect();
+
+		Self::from_associations(&associations)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn keys_are_validated() {
+		assert!(SubstitutionKey::from_table([0; ALPHABET_LEN]).is_none());
+		assert!("ABC".parse::<SubstitutionKey>().is_err());
+		assert!("AACDEFGHIJKLMNOPQRSTUVWXYZ".parse::<SubstitutionKey>().is_err());
+		assert!(matches!("A1CDEFGHIJKLMNOPQRSTUVWXYZ".parse::<SubstitutionKey>(), Err(Error::UnsupportedSymbol('1'))));
+
+		let partial: SubstitutionKey = "Q-ERTYUIOPASDFGHJKLZXCVBN?".parse().unwrap();
+		assert!(!partial.is_complete());
+		assert_eq!(partial.to_string(), "Q-ERTYUIOPASDFGHJKLZXCVBN-");
+		assert_eq!(partial.apply("Abz, cab!"), "Qbz, eqb!");
+		assert_eq!(partial.text_associations("Abz, cab!"), vec![('A', 'Q'), ('B', '?'), ('C', 'E'), ('Z', '?')]);
+	}
+
+	#[test]
+	fn keys_are_inverted_and_composed() {
+		let key: SubstitutionKey = "QWERTYUIOPASDFGHJKLZXCVBNM".parse().unwrap();
+		let inverse = key.inverse();
+
+		assert_eq!(inverse.apply(&key.apply("Hello, World")), "Hello, World");
+		assert_eq!(key.compose(&inverse), SubstitutionKey::identity());
+		assert_eq!(key.compose(&key).apply("abc"), key.apply(&key.apply("abc")));
+
+		let partial = SubstitutionKey::from_associations(&[('A', 'Q'), ('B', '?')]).unwrap();
+		assert_eq!(partial.inverse().associations(), vec![('Q', 'A')]);
+		assert_eq!(partial.compose(&key).associations(), vec![('A', 'J')]);
+
+		let mut set = partial.clone();
+		set.set(2, Some(16));
+		assert_eq!(set.text_associations("abc"), vec![('A', '?'), ('B', '?'), ('C', 'Q')]);
+	}
+}
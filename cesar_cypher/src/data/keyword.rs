@@ -0,0 +1,223 @@
+use super::{key::SubstitutionKey, ALPHABET_LEN};
+
+/// This struct represents the keyword a substitution key was most likely
+/// generated from, that is, the keyed alphabet made of the letters of the
+/// keyword followed by the rest of the alphabet in order, written under the
+/// plain alphabet from a given position on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordKey {
+	/// The letters of the keyword, without repetitions, with a `?` for the
+	/// ones the key does not tell.
+	pub keyword: String,
+	/// The position of the plain alphabet the keyed alphabet starts at.
+	pub shift: usize,
+	/// The key, with the unknown letters the structure tells filled in.
+	pub key: SubstitutionKey,
+	/// The amount of letters filled in from the structure.
+	pub filled: usize
+}
+
+impl SubstitutionKey {
+	/// Infers the keyword and shift that most likely generated this key, and
+	/// fills the unknown letters of the key that follow from them.
+	///
+	/// Every shift is tried, looking for the shortest keyword whose remaining
+	/// letters, sorted, match the rest of the keyed alphabet. A shift counts
+	/// as one more letter of the keyword, as a keyword ending by the last
+	/// letters of the alphabet can also be read as a shorter one, shifted.
+	/// The unknown letters are only filled when every completion of the
+	/// structure agrees on them. Gives back nothing when the key knows no
+	/// letter at all.
+	pub fn recover_keyword(&self) -> Option<KeywordKey> {
+		// the keyed alphabet is the one that cyphers the plain letters
+		let cypher = self.inverse();
+		if (0..ALPHABET_LEN as u8).all(|p| cypher.get(p).is_none()) {
+			return None;
+		}
+
+		let (shift, len, filled) = (0..ALPHABET_LEN)
+			.flat_map(|shift| {
+				let keyed: Vec<Option<u8>> = (0..ALPHABET_LEN)
+					.map(|i| cypher.get(((i + shift) % ALPHABET_LEN) as u8))
+					.collect();
+				(0..=ALPHABET_LEN)
+					.find_map(|len| complete_keyed(&keyed, len))
+					.map(|(len, filled)| (shift, len, filled))
+			})
+			.min_by_key(|&(shift, len, _)| (len + (shift != 0) as usize, shift))?;
+
+		let mut table = [None; ALPHABET_LEN];
+		for (i, c) in filled.iter().enumerate() {
+			if let Some(c) = c {
+				table[*c as usize] = Some(((i + shift) % ALPHABET_LEN) as u8);
+			}
+		}
+		let key = SubstitutionKey::partial(table)?;
+		let known = |k: &SubstitutionKey| (0..ALPHABET_LEN as u8).filter(|&c| k.get(c).is_some()).count();
+
+		Some(KeywordKey {
+			keyword: filled[..len].iter().map(|c| c.map_or('?', |c| (c + b'A') as char)).collect(),
+			shift,
+			filled: known(&key) - known(self),
+			key
+		})
+	}
+}
+
+/// Tries to complete the given keyed alphabet, with a keyword of the given
+/// length, giving back the completed alphabet when it is consistent with it.
+///
+/// The rest of the alphabet has to hold the letters missing from the keyword
+/// in order, so the letters of the alphabet that are not known anywhere
+/// are placed, with the search of every consistent placement, either after
+/// the keyword or inside of it.
+fn complete_keyed(keyed: &[Option<u8>], len: usize) -> Option<(usize, Vec<Option<u8>>)> {
+	let (head, tail) = keyed.split_at(len);
+
+	let mut known = [false; ALPHABET_LEN];
+	for c in keyed.iter().flatten() {
+		known[*c as usize] = true;
+	}
+	let missing = |c: u8| !known[c as usize];
+
+	// the rest of the alphabet, along with the letters of the keyword that
+	// are still unknown, which have to be dropped from it
+	let rest: Vec<u8> = (0..ALPHABET_LEN as u8).filter(|c| !head.contains(&Some(*c))).collect();
+	let dropped = rest.len() - tail.len();
+
+	// forward[i][j] tells whether the first `i` letters of the rest can be
+	// placed over the first `j` letters of the tail, and backward the same
+	// for the last ones
+	let fits = |i: usize, j: usize| tail[j].map_or(missing(rest[i]), |c| c == rest[i]);
+	let (n, m) = (rest.len(), tail.len());
+	let mut forward = vec![vec![false; m + 1]; n + 1];
+	forward[0][0] = true;
+	for i in 0..n {
+		for j in 0..=m.min(i) {
+			if !forward[i][j] { continue; }
+			if j < m && fits(i, j) { forward[i + 1][j + 1] = true; }
+			if i - j < dropped && missing(rest[i]) { forward[i + 1][j] = true; }
+		}
+	}
+	if !forward[n][m] {
+		return None;
+	}
+
+	let mut backward = vec![vec![false; m + 1]; n + 1];
+	backward[n][m] = true;
+	for i in (0..n).rev() {
+		for j in (0..=m.min(i)).rev() {
+			backward[i][j] = (j < m && fits(i, j) && backward[i + 1][j + 1])
+				|| (i - j < dropped && missing(rest[i]) && backward[i + 1][j]);
+		}
+	}
+
+	// a letter is only filled in when every placement agrees on it
+	let mut choices: Vec<Vec<u8>> = vec![Vec::new(); m];
+	let mut dropped_choices: Vec<u8> = Vec::new();
+	for i in 0..n {
+		for j in 0..=m.min(i) {
+			if !forward[i][j] { continue; }
+			if j < m && fits(i, j) && backward[i + 1][j + 1] && !choices[j].contains(&rest[i]) {
+				choices[j].push(rest[i]);
+			}
+			if i - j < dropped && missing(rest[i]) && backward[i + 1][j] && !dropped_choices.contains(&rest[i]) {
+				dropped_choices.push(rest[i]);
+			}
+		}
+	}
+
+	let mut filled: Vec<Option<u8>> = head.to_vec();
+	// a single unknown letter of the keyword can only be the dropped one
+	if dropped == 1 && dropped_choices.len() == 1 {
+		if let Some(slot) = filled.iter_mut().find(|c| c.is_none()) {
+			*slot = Some(dropped_choices[0]);
+		}
+	}
+	filled.extend(tail.iter().zip(choices.iter()).map(|(c, choices)| match choices.as_slice() {
+		[only] => Some(*only),
+		_ => *c
+	}));
+
+	Some((len, filled))
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{rngs::StdRng, SeedableRng};
+
+	use super::*;
+	use super::super::{model::LanguageModel, Analizer};
+	use crate::cipher::{Cipher, Keyword};
+
+	/// Recovers the decrypting key of the keyword cypher from the way it
+	/// cyphers the alphabet.
+	fn keyword_key(keyword: &str) -> SubstitutionKey {
+		let keyed = Keyword::new(keyword).unwrap().encrypt("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+		let associations: Vec<_> = keyed.chars().zip('A'..='Z').collect();
+
+		SubstitutionKey::from_associations(&associations).unwrap()
+	}
+
+	#[test]
+	fn keyword_is_recovered() {
+		let key = keyword_key("zebras");
+		let recovered = key.recover_keyword().unwrap();
+
+		assert_eq!(recovered.keyword, "ZEBRAS");
+		assert_eq!(recovered.shift, 0);
+		assert_eq!(recovered.key, key);
+		assert_eq!(recovered.filled, 0);
+	}
+
+	#[test]
+	fn shifted_keyword_is_recovered() {
+		// the keyed alphabet written under the plain one from the "D" on
+		let key = keyword_key("kangaroo");
+		let shifted = SubstitutionKey::from_associations(
+			&key.associations().into_iter().map(|(c, p)| (c, ((p as u8 - b'A' + 3) % 26 + b'A') as char)).collect::<Vec<_>>()
+		).unwrap();
+
+		let recovered = shifted.recover_keyword().unwrap();
+
+		assert_eq!(recovered.keyword, "KANGRO");
+		assert_eq!(recovered.shift, 3);
+		assert_eq!(recovered.key, shifted);
+	}
+
+	#[test]
+	fn unknown_letters_are_filled() {
+		let key = keyword_key("zebras");
+		// the plain letters of "E", "M", "U" and "V" are unknown
+		let partial = SubstitutionKey::from_associations(
+			&key.associations().into_iter().map(|(c, p)| (c, if "EMUV".contains(c) { '?' } else { p })).collect::<Vec<_>>()
+		).unwrap();
+
+		let recovered = partial.recover_keyword().unwrap();
+
+		assert_eq!(recovered.keyword, "ZEBRAS");
+		assert_eq!(recovered.key, key);
+		assert_eq!(recovered.filled, 4);
+		assert!(SubstitutionKey::from_associations(&[]).unwrap().recover_keyword().is_none());
+	}
+
+	#[test]
+	fn keyword_of_a_cracked_key_is_recovered() {
+		let plain = "IT IS A TRUTH UNIVERSALLY ACKNOWLEDGED THAT A SINGLE MAN IN POSSESSION OF A GOOD \
+			FORTUNE MUST BE IN WANT OF A WIFE HOWEVER LITTLE KNOWN THE FEELINGS OR VIEWS OF SUCH A MAN MAY BE";
+		let cyphered = Keyword::new("zebras").unwrap().encrypt(plain);
+		let analizer = Analizer::new(LanguageModel::default());
+
+		let solution = analizer.crack_substitution_with(&cyphered, 3, &mut StdRng::seed_from_u64(42)).unwrap();
+		assert_eq!(solution.text, plain);
+
+		// the letters missing from the text are left unknown, or else their
+		// guessed plain letters would break the keyed alphabet
+		let known = SubstitutionKey::from_associations(&solution.key.text_associations(&cyphered)).unwrap();
+		let recovered = known.recover_keyword().unwrap();
+
+		assert_eq!(recovered.keyword, "ZEBRAS");
+		assert_eq!(recovered.shift, 0);
+		assert!(recovered.key.associations().iter().all(|a| keyword_key("zebras").associations().contains(a)));
+	}
+}
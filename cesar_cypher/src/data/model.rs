@@ -0,0 +1,336 @@
+use std::{fs, path::Path, str::FromStr};
+
+use crate::error::Error;
+
+use super::fold_case;
+
+/// The bundled language models, embedded into the binary at compile time.
+const ENGLISH_MODEL: &str = include_str!("../../models/english.txt");
+const SPANISH_MODEL: &str = include_str!("../../models/spanish.txt");
+const FRENCH_MODEL: &str = include_str!("../../models/french.txt");
+const GERMAN_MODEL: &str = include_str!("../../models/german.txt");
+
+/// The languages whose models are bundled with the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+	English,
+	Spanish,
+	French,
+	German
+}
+
+impl Language {
+	pub const VARIANTS: &'static [&'static str] = &["english", "spanish", "french", "german"];
+	pub const ALL: [Self; 4] = [Self::English, Self::Spanish, Self::French, Self::German];
+}
+
+impl FromStr for Language {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"english" => Ok(Self::English),
+			"spanish" => Ok(Self::Spanish),
+			"french" => Ok(Self::French),
+			"german" => Ok(Self::German),
+			unk => Err(format!(r#"unknown language "{unk}""#))
+		}
+	}
+}
+
+/// This struct represents the frequency analysis of a language, used as the
+/// reference the cyphered texts are compared against.
+///
+/// A model is read from a plain text format made of a `name = <Name>` line
+/// followed by up to three sections:
+///
+/// ```text
+/// # comments start with a hash
+/// name = English
+///
+/// [letters]
+/// E 11.160
+/// A 8.4966
+///
+/// [bigrams]
+/// TH 3.56
+///
+/// [quadgrams]
+/// TION 0.47
+///
+/// [words]
+/// THE
+/// ```
+///
+/// The `[letters]` section is mandatory, while the `[bigrams]`, `[quadgrams]`
+/// and `[words]` ones are optional.
+///
+/// The frequencies are percentages, unless the header holds a `scale = log10`
+/// line, in which case they are the base 10 logarithm of the probability of
+/// each n-gram, as written by the `train` sub-command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageModel {
+	name: String,
+	letters: Vec<(char, f64)>,
+	bigrams: Vec<(String, f64)>,
+	quadgrams: Vec<(String, f64)>,
+	words: Vec<String>
+}
+
+/// The sections a model file is made of.
+enum Section {
+	Header,
+	Letters,
+	Bigrams,
+	Quadgrams,
+	Words
+}
+
+/// The scales the frequencies of a model file can be expressed in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Scale {
+	Percent,
+	Log10
+}
+
+impl LanguageModel {
+	/// Retrieves the model bundled with the program for the given language.
+	pub fn bundled(language: Language) -> Self {
+		let src = match language {
+			Language::English => ENGLISH_MODEL,
+			Language::Spanish => SPANISH_MODEL,
+			Language::French => FRENCH_MODEL,
+			Language::German => GERMAN_MODEL
+		};
+
+		Self::parse(src).expect("bundled language models are valid")
+	}
+
+	/// Loads a model from the file at the given path.
+	///
+	/// If the file does not specify a name, the name of the file is used instead.
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		let path = path.as_ref();
+		let mut model = Self::parse(&fs::read_to_string(path)?)?;
+		if model.name.is_empty() {
+			model.name = path
+				.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default();
+		}
+
+		Ok(model)
+	}
+
+	/// Parses a model from its textual representation.
+	pub fn parse(src: &str) -> Result<Self, Error> {
+		let mut model = Self {
+			name: String::new(),
+			letters: Vec::new(),
+			bigrams: Vec::new(),
+			quadgrams: Vec::new(),
+			words: Vec::new()
+		};
+
+		let mut section = Section::Header;
+		let mut scale = Scale::Percent;
+		for (n, line) in src.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') { continue; }
+
+			let invalid = |msg: &str| Error::InvalidModel(format!("line {}: {msg}", n + 1));
+
+			let next_section = match line {
+				"[letters]" => Some(Section::Letters),
+				"[bigrams]" => Some(Section::Bigrams),
+				"[quadgrams]" => Some(Section::Quadgrams),
+				"[words]" => Some(Section::Words),
+				_ => None
+			};
+			if let Some(next_section) = next_section {
+				section = next_section;
+				continue;
+			}
+
+			match section {
+				Section::Header => {
+					let (key, val) = line
+						.split_once('=')
+						.ok_or_else(|| invalid("expected a `name = <Name>` entry"))?;
+					match (key.trim(), val.trim()) {
+						("name", name) => model.name = name.to_string(),
+						("scale", "percent") => scale = Scale::Percent,
+						("scale", "log10") => scale = Scale::Log10,
+						("scale", _) => return Err(invalid("expected a `percent` or `log10` scale")),
+						_ => return Err(invalid("unknown header entry"))
+					}
+				},
+				Section::Letters => {
+					let (gram, freq) = Self::parse_entry(line, scale).ok_or_else(|| invalid("malformed letter entry"))?;
+					let mut chars = gram.chars();
+					match (chars.next(), chars.next()) {
+						(Some(c), None) if c.is_alphabetic() => model.letters.push((c, freq)),
+						(Some(c), None) => return Err(Error::UnsupportedSymbol(c)),
+						_ => return Err(invalid("expected a single letter"))
+					}
+				},
+				Section::Bigrams => {
+					let (gram, freq) = Self::parse_entry(line, scale).ok_or_else(|| invalid("malformed bigram entry"))?;
+					if gram.chars().count() != 2 {
+						return Err(invalid("expected a pair of letters"));
+					}
+					model.bigrams.push((gram, freq));
+				},
+				Section::Quadgrams => {
+					let (gram, freq) = Self::parse_entry(line, scale).ok_or_else(|| invalid("malformed quadgram entry"))?;
+					if gram.chars().count() != 4 {
+						return Err(invalid("expected four letters"));
+					}
+					model.quadgrams.push((gram, freq));
+				},
+				Section::Words => model.words.push(line.chars().map(fold_case).collect())
+			}
+		}
+
+		Self::new(model.name, model.letters, model.bigrams, model.quadgrams, model.words)
+	}
+
+	/// Creates a model out of the frequencies, as percentages, of the letters
+	/// and n-grams of the language, along with its common words.
+	pub fn new(
+		name: String,
+		mut letters: Vec<(char, f64)>,
+		mut bigrams: Vec<(String, f64)>,
+		mut quadgrams: Vec<(String, f64)>,
+		words: Vec<String>
+	) -> Result<Self, Error>
+	{
+		if letters.is_empty() {
+			return Err(Error::InvalidModel("the [letters] section is missing or empty".to_owned()));
+		}
+
+		// the analysis relies on the letters being sorted by their frequency
+		letters.sort_by(|a, b| b.1.total_cmp(&a.1));
+		bigrams.sort_by(|a, b| b.1.total_cmp(&a.1));
+		quadgrams.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+		Ok(Self { name, letters, bigrams, quadgrams, words })
+	}
+
+	/// Parses a `<gram> <frequency>` entry, ensuring the frequency is valid,
+	/// and turns it into a percentage.
+	fn parse_entry(line: &str, scale: Scale) -> Option<(String, f64)> {
+		let mut parts = line.split_whitespace();
+		let gram = parts.next()?.chars().map(fold_case).collect();
+		let value: f64 = parts.next()?.parse().ok()?;
+		let valid = value.is_finite() && match scale {
+			Scale::Percent => value >= 0.0,
+			Scale::Log10 => value <= 0.0
+		};
+		let freq = match scale {
+			Scale::Percent => value,
+			Scale::Log10 => 10f64.powf(value) * 100.0
+		};
+		match (parts.next(), valid) {
+			(None, true) => Some((gram, freq)),
+			_ => None
+		}
+	}
+
+	/// Writes the model in its textual representation, with the frequencies
+	/// as log-probabilities, so it can be parsed back.
+	pub fn to_text(&self) -> String {
+		let mut text = String::new();
+		if !self.name.is_empty() {
+			text.push_str(&format!("name = {}\n", self.name));
+		}
+		text.push_str("scale = log10\n");
+
+		let entries = |grams: &[(String, f64)]| -> String {
+			grams
+				.iter()
+				.map(|(g, f)| format!("{g} {:.6}\n", (f / 100.0).log10()))
+				.collect()
+		};
+		let letters: Vec<_> = self.letters.iter().map(|(c, f)| (c.to_string(), *f)).collect();
+		text.push_str(&format!("\n[letters]\n{}", entries(&letters)));
+		if !self.bigrams.is_empty() {
+			text.push_str(&format!("\n[bigrams]\n{}", entries(&self.bigrams)));
+		}
+		if !self.quadgrams.is_empty() {
+			text.push_str(&format!("\n[quadgrams]\n{}", entries(&self.quadgrams)));
+		}
+		if !self.words.is_empty() {
+			text.push_str(&format!("\n[words]\n{}\n", self.words.join("\n")));
+		}
+
+		text
+	}
+
+	/// Retrieves the name of the language the model belongs to.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Retrieves the letters of the language, sorted by their frequency.
+	pub fn letters(&self) -> &[(char, f64)] {
+		&self.letters
+	}
+
+	/// Retrieves the bigrams of the language, sorted by their frequency.
+	pub fn bigrams(&self) -> &[(String, f64)] {
+		&self.bigrams
+	}
+
+	/// Retrieves the quadgrams of the language, sorted by their frequency.
+	pub fn quadgrams(&self) -> &[(String, f64)] {
+		&self.quadgrams
+	}
+
+	/// Retrieves the list of common words of the language.
+	pub fn words(&self) -> &[String] {
+		&self.words
+	}
+}
+
+impl Default for LanguageModel {
+	fn default() -> Self {
+		Self::bundled(Language::English)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bundled_models_are_valid() {
+		for lang in Language::VARIANTS {
+			let model = LanguageModel::bundled(lang.parse().unwrap());
+
+			assert!(model.letters().len() >= 26);
+			assert!(!model.bigrams().is_empty());
+			assert!(!model.words().is_empty());
+		}
+	}
+
+	#[test]
+	fn model_is_parsed() {
+		let model = LanguageModel::parse("name = Tiny\n[letters]\nb 1.5\nA 3\n[words]\nab").unwrap();
+
+		assert_eq!(model.name(), "Tiny");
+		assert_eq!(model.letters(), &[('A', 3.0), ('B', 1.5)]);
+		assert!(model.bigrams().is_empty());
+		assert_eq!(model.words(), &["AB".to_string()]);
+	}
+
+	#[test]
+	fn invalid_model_is_rejected() {
+		assert!(LanguageModel::parse("name = Empty").is_err());
+		assert!(LanguageModel::parse("[letters]\nAB 1.0").is_err());
+		assert!(LanguageModel::parse("[letters]\nA -1.0").is_err());
+		assert!(matches!(LanguageModel::parse("[letters]\n1 1.0"), Err(Error::UnsupportedSymbol('1'))));
+		assert!(LanguageModel::parse("[bigrams]\nABC 1.0\n[letters]\nA 1").is_err());
+		assert!(LanguageModel::parse("[letters]\nA 1\n[quadgrams]\nTHE 1.0").is_err());
+	}
+}
@@ -0,0 +1,273 @@
+use std::{collections::{HashMap, HashSet}, fs, path::Path};
+
+use crate::error::Error;
+
+use super::{key::SubstitutionKey, require_letters, Analizer, ALPHABET_LEN};
+
+/// Maximum amount of nodes visited by the search before giving up on finding
+/// a better key than the best one found so far.
+const SEARCH_BUDGET: usize = 200_000;
+
+/// This struct represents a dictionary whose words are indexed by their letter
+/// pattern, that is, the order in which their letters first appear, so that
+/// `HELLO` and `CARRY` share the `ABCCD` pattern.
+#[derive(Debug, Clone, Default)]
+pub struct WordPatterns {
+	index: HashMap<String, Vec<String>>
+}
+
+impl WordPatterns {
+	/// Builds the index from the given words, discarding the ones that contain
+	/// characters other than latin letters.
+	pub fn from_words<I, S>(words: I) -> Self
+		where
+			I: IntoIterator<Item = S>,
+			S: AsRef<str>
+	{
+		let mut seen = HashSet::new();
+		let mut index: HashMap<String, Vec<String>> = HashMap::new();
+		for word in words {
+			let word = word.as_ref().trim().to_uppercase();
+			if word.is_empty() || !word.chars().all(|c| c.is_ascii_uppercase()) { continue; }
+			if seen.insert(word.clone()) {
+				index.entry(letter_pattern(&word)).or_default().push(word);
+			}
+		}
+
+		Self { index }
+	}
+
+	/// Loads the index from a wordlist file, with a word per line.
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		Ok(Self::from_words(fs::read_to_string(path)?.lines()))
+	}
+
+	/// Retrieves the words that share the pattern of the given one.
+	fn matches(&self, word: &str) -> &[String] {
+		self.index
+			.get(&letter_pattern(word))
+			.map_or(&[], |w| w.as_slice())
+	}
+}
+
+/// This struct represents the key found by the word-pattern attack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternSolution {
+	/// The partial key found, which leaves unknown the letters that could not
+	/// be resolved.
+	pub key: SubstitutionKey,
+	/// The amount of words of the text fully resolved by the key.
+	pub matched_words: usize,
+	/// The amount of words inside the text.
+	pub total_words: usize
+}
+
+/// The state of the search for a consistent key.
+///
+/// Every distinct cyphered word is weighted by the amount of letters it covers
+/// inside the text, so the search prefers the keys that resolve the longest
+/// and most repeated words.
+struct Search<'a> {
+	words: Vec<Word<'a>>,
+	forward: [Option<u8>; ALPHABET_LEN],
+	backward: [Option<u8>; ALPHABET_LEN],
+	assigned: Vec<bool>,
+	matched: usize,
+	best: ([Option<u8>; ALPHABET_LEN], usize),
+	visited: usize
+}
+
+/// A distinct cyphered word along with its weight and dictionary choices.
+struct Word<'a> {
+	text: &'a str,
+	weight: usize,
+	choices: &'a [String]
+}
+
+impl Analizer {
+	/// Searches a substitution key that turns as many words of the given text
+	/// as possible into words of the dictionary, keeping the key consistent
+	/// over all of them.
+	///
+	/// The search always continues with the word that has the fewest
+	/// dictionary words consistent with the key built so far, so the words
+	/// with a single choice propagate their letters before any guess is made.
+	pub fn crack_patterns(&self, text: &str, dictionary: &WordPatterns) -> Result<PatternSolution, Error> {
+		require_letters(text)?;
+		let text = text.to_ascii_uppercase();
+		let mut cyphered: Vec<&str> = text
+			.split(|c: char| !c.is_ascii_alphabetic())
+			.filter(|w| !w.is_empty())
+			.collect();
+		cyphered.sort_unstable();
+		let total_words = cyphered.len();
+
+		let mut words: Vec<Word> = Vec::new();
+		for w in cyphered {
+			match words.last_mut() {
+				Some(last) if last.text == w => last.weight += w.len(),
+				_ => words.push(Word { text: w, weight: w.len(), choices: dictionary.matches(w) })
+			}
+		}
+
+		let mut search = Search {
+			assigned: vec![false; words.len()],
+			words,
+			forward: [None; ALPHABET_LEN],
+			backward: [None; ALPHABET_LEN],
+			matched: 0,
+			best: ([None; ALPHABET_LEN], 0),
+			visited: 0
+		};
+		search.run();
+		let best = search.best.0;
+
+		Ok(PatternSolution {
+			key: SubstitutionKey::partial(best).expect("the search keeps the key consistent"),
+			matched_words: search.words
+				.iter()
+				.filter(|w| w.text.bytes().all(|c| best[(c - b'A') as usize].is_some()))
+				.map(|w| w.weight / w.text.len())
+				.sum(),
+			total_words
+		})
+	}
+}
+
+impl<'a> Search<'a> {
+	/// Explores the assignments of the remaining words, keeping track of the
+	/// key that matches the heaviest set of words.
+	fn run(&mut self) {
+		self.visited += 1;
+		if self.matched > self.best.1 {
+			self.best = (self.forward, self.matched);
+		}
+		if self.visited > SEARCH_BUDGET { return; }
+
+		// picking the remaining word with the fewest consistent choices, while
+		// bounding how many more words could still be matched
+		let mut next: Option<(usize, Vec<&'a String>)> = None;
+		let mut reachable = 0;
+		for (i, word) in self.words.iter().enumerate() {
+			if self.assigned[i] { continue; }
+			let consistent: Vec<_> = word.choices.iter().filter(|c| self.fits(word.text, c)).collect();
+			if consistent.is_empty() { continue; }
+
+			reachable += word.weight;
+			let fewer = match &next {
+				Some((_, n)) => consistent.len() < n.len(),
+				None => true
+			};
+			if fewer {
+				next = Some((i, consistent));
+			}
+		}
+
+		let (i, choices) = match next {
+			Some(next) if self.matched + reachable > self.best.1 => next,
+			_ => return
+		};
+
+		let (word, weight) = (self.words[i].text, self.words[i].weight);
+		self.assigned[i] = true;
+		for choice in choices {
+			let (forward, backward) = (self.forward, self.backward);
+			self.bind(word, choice);
+			self.matched += weight;
+			self.run();
+			self.matched -= weight;
+			(self.forward, self.backward) = (forward, backward);
+		}
+
+		// the word may not be in the dictionary at all, so the rest of them
+		// are also tried without it
+		self.run();
+		self.assigned[i] = false;
+	}
+
+	/// Determines whether the cyphered word can become the given plain word
+	/// without contradicting the current key.
+	fn fits(&self, cyphered: &str, plain: &str) -> bool {
+		let mut forward = self.forward;
+		let mut backward = self.backward;
+		for (c, p) in cyphered.bytes().zip(plain.bytes()) {
+			let (c, p) = ((c - b'A') as usize, p - b'A');
+			match (forward[c], backward[p as usize]) {
+				(Some(f), _) if f != p => return false,
+				(_, Some(b)) if b as usize != c => return false,
+				_ => {
+					forward[c] = Some(p);
+					backward[p as usize] = Some(c as u8);
+				}
+			}
+		}
+
+		true
+	}
+
+	/// Extends the key with the letters of the given pair of words.
+	fn bind(&mut self, cyphered: &str, plain: &str) {
+		for (c, p) in cyphered.bytes().zip(plain.bytes()) {
+			let (c, p) = (c - b'A', p - b'A');
+			self.forward[c as usize] = Some(p);
+			self.backward[p as usize] = Some(c);
+		}
+	}
+}
+
+/// Computes the letter pattern of the given word.
+fn letter_pattern(word: &str) -> String {
+	let mut seen: Vec<char> = Vec::new();
+	word
+		.chars()
+		.map(|c| {
+			let pos = seen.iter().position(|&s| s == c).unwrap_or_else(|| {
+				seen.push(c);
+				seen.len() - 1
+			});
+			(b'A' + pos as u8) as char
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::model::LanguageModel;
+
+	#[test]
+	fn patterns_are_computed() {
+		assert_eq!(letter_pattern("HELLO"), "ABCCD");
+		assert_eq!(letter_pattern("THAT"), "ABCA");
+	}
+
+	#[test]
+	fn patterns_are_cracked() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let dictionary = WordPatterns::from_words(
+			["THAT", "WHAT", "HILL", "WILL", "TELL", "IS", "ON", "THE", "TALLEST"]
+		);
+
+		// "THAT HILL IS THE TALLEST" shifted by one
+		let cyphered = "UIBU IJMM JT UIF UBMMFTU";
+		let solution = analizer.crack_patterns(cyphered, &dictionary).unwrap();
+		let result = analizer.assemble_result(cyphered, &solution.key.text_associations(cyphered)).unwrap();
+
+		assert_eq!(result, "THAT HILL IS THE TALLEST");
+		assert_eq!(solution.matched_words, solution.total_words);
+	}
+
+	#[test]
+	fn unknown_words_are_left_unresolved() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let dictionary = WordPatterns::from_words(["THE", "HELLO"]);
+
+		let cyphered = "uif Ifmmp xyzzy!";
+		let solution = analizer.crack_patterns(cyphered, &dictionary).unwrap();
+		let result = analizer.assemble_result(cyphered, &solution.key.text_associations(cyphered)).unwrap();
+
+		assert_eq!(result, "the Hello ?????!");
+		assert_eq!(solution.matched_words, 2);
+		assert_eq!(solution.total_words, 3);
+	}
+}
@@ -0,0 +1,130 @@
+use std::f64::consts::LN_10;
+
+use crate::error::Error;
+
+use super::{fitness::NgramFitness, key::SubstitutionKey, rank_candidates, Analizer, Candidate, ALPHABET_LEN};
+
+/// Amount of partial keys kept by the beam search at every step.
+const BEAM_WIDTH: usize = 32;
+
+/// A partial key, holding the plain letter of every cyphered letter of the
+/// text, along with its score.
+#[derive(Clone)]
+struct Beam {
+	key: SubstitutionKey,
+	score: f64,
+	count: usize
+}
+
+impl Analizer {
+	/// Completes the given partial key into the `top` most likely full
+	/// decryptions of the given text.
+	///
+	/// The letters the key knows are kept as they are, while the unknown ones
+	/// are assigned the remaining letters of the language by a beam search
+	/// over the n-gram fitness of the resulting text. The score of every
+	/// candidate is its fitness normalized by its amount of n-grams, negated
+	/// so the lower, the better.
+	pub fn rank_decryptions(
+		&self,
+		text: &str,
+		seed: &SubstitutionKey,
+		top: usize
+	) -> Result<Vec<Candidate>, Error>
+	{
+		let stream: Vec<u8> = text
+			.chars()
+			.filter_map(|c| self.alphabet.normalize(c))
+			.filter(char::is_ascii_uppercase)
+			.map(|c| c as u8 - b'A')
+			.collect();
+		if stream.is_empty() {
+			return Err(Error::EmptyInput);
+		}
+
+		let mut counts = [0; ALPHABET_LEN];
+		for &c in stream.iter() { counts[c as usize] += 1; }
+		let mut open: Vec<u8> = (0..ALPHABET_LEN as u8)
+			.filter(|&c| counts[c as usize] > 0 && seed.get(c).is_none())
+			.collect();
+		open.sort_by(|&a, &b| counts[b as usize].cmp(&counts[a as usize]));
+
+		let fitness = NgramFitness::new(&self.model);
+		let score = |key: SubstitutionKey| {
+			let letters: Vec<Option<u8>> = stream.iter().map(|&c| key.get(c)).collect();
+			let (score, count) = fitness.score_known(&letters);
+			Beam { key, score, count }
+		};
+
+		let mut beams = vec![score(seed.clone())];
+		for &c in open.iter() {
+			let mut next: Vec<Beam> = Vec::new();
+			for beam in beams.iter() {
+				let used = beam.key.inverse();
+				let free = self.model
+					.letters()
+					.iter()
+					.filter(|(p, _)| p.is_ascii_uppercase())
+					.map(|(p, _)| *p as u8 - b'A')
+					.filter(|&p| used.get(p).is_none());
+				for p in free {
+					let mut key = beam.key.clone();
+					key.set(c, Some(p));
+					next.push(score(key));
+				}
+			}
+
+			// running out of letters leaves the rest of them unknown
+			if next.is_empty() { break; }
+			next.sort_by(|a, b| b.score.total_cmp(&a.score));
+			next.truncate(BEAM_WIDTH);
+			beams = next;
+		}
+
+		let count = beams.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+		let mut candidates = beams
+			.into_iter()
+			.take(top.max(1))
+			.map(|beam| {
+				Ok(Candidate {
+					text: self.assemble_result(text, &beam.key.text_associations(text))?,
+					key: beam.key.to_string(),
+					score: -beam.score / beam.count.max(1) as f64,
+					confidence: 0.0
+				})
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		// the normalized scores are turned back into log-likelihoods
+		rank_candidates(&mut candidates, LN_10 * count as f64);
+
+		Ok(candidates)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::model::LanguageModel;
+
+	#[test]
+	fn unknown_letters_are_completed() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let text = "Wkh zruog";
+
+		// every letter but the shifted "E" and "O" is left to the search
+		let associations: Vec<_> = "WKHZRUOG"
+			.chars()
+			.map(|c| (c, match c { 'H' => 'E', 'R' => 'O', _ => '?' }))
+			.collect();
+		let seed = SubstitutionKey::from_associations(&associations).unwrap();
+		let candidates = analizer.rank_decryptions(text, &seed, 3).unwrap();
+
+		assert_eq!(candidates.len(), 3);
+		assert!(candidates.iter().all(|c| !c.text.contains('?')));
+		assert!(candidates.iter().all(|c| c.text.chars().nth(2) == Some('e') && c.text.chars().nth(5) == Some('o')));
+		assert_eq!(candidates[0].key.len(), 26);
+		assert!(candidates[0].score <= candidates[1].score);
+		assert!(candidates.windows(2).all(|w| w[0].confidence >= w[1].confidence));
+	}
+}
@@ -0,0 +1,47 @@
+use crate::error::Error;
+
+use super::{chi_squared, letter_counts, rank_candidates, require_letters, Analizer, Candidate, ALPHABET_LEN};
+
+impl Analizer {
+	/// Tries every possible rotation of the alphabet over the given text and
+	/// ranks the resulting plaintexts by the chi-squared distance between their
+	/// letters distribution and the language's frequency analysis.
+	///
+	/// The returned candidates are sorted from the best to the worst one, so the
+	/// first of them holds the most likely key.
+	pub fn crack_shift(&self, text: &str) -> Result<Vec<Candidate>, Error> {
+		require_letters(text)?;
+		let expected = self.letters_table();
+		let mut candidates: Vec<_> = (0..ALPHABET_LEN as u8)
+			.map(|shift| {
+				// decrypting is just shifting the remaining positions forward
+				let plain = shift_text(text, ALPHABET_LEN as u8 - shift);
+				Candidate {
+					key: format!("{shift} ({})", (b'A' + shift) as char),
+					score: chi_squared(&letter_counts(&plain), &expected),
+					text: plain,
+					confidence: 0.0
+				}
+			})
+			.collect();
+
+		// the chi-squared statistic is twice the negative log-likelihood ratio
+		rank_candidates(&mut candidates, 0.5);
+
+		Ok(candidates)
+	}
+}
+
+/// Shifts forward every letter of the given text by `shift` positions,
+/// leaving the rest of the characters untouched.
+pub fn shift_text(text: &str, shift: u8) -> String {
+	let shift = shift % ALPHABET_LEN as u8;
+	text
+		.chars()
+		.map(|c| match c {
+			'A'..='Z' => ((c as u8 - b'A' + shift) % ALPHABET_LEN as u8 + b'A') as char,
+			'a'..='z' => ((c as u8 - b'a' + shift) % ALPHABET_LEN as u8 + b'a') as char,
+			_ => c
+		})
+		.collect()
+}
@@ -0,0 +1,147 @@
+use crate::error::Error;
+
+use super::Analizer;
+
+/// Lengths of the n-grams searched for repetitions.
+const REPEAT_LENGTHS: [usize; 3] = [3, 4, 5];
+
+/// This struct represents a sequence of letters repeated along a text, along
+/// with the spacings between its consecutive occurrences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repeat {
+	pub sequence: String,
+	pub count: usize,
+	pub spacings: Vec<usize>
+}
+
+/// This struct represents the statistics of a text, used to tell which kind
+/// of cypher produced it before choosing an attack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStats {
+	/// Amount of letters of the alphabet in the text.
+	pub length: usize,
+	/// Amount of distinct letters of the alphabet in the text.
+	pub distinct_letters: usize,
+	/// Amount of distinct symbols in the text, whitespace aside.
+	pub distinct_symbols: usize,
+	/// Shannon entropy of the letters of the text, in bits per letter.
+	pub entropy: f64,
+	/// Index of coincidence of the letters of the text.
+	pub coincidence: f64,
+	/// Shannon entropy of the letters of the language, in bits per letter.
+	pub language_entropy: f64,
+	/// Index of coincidence of the letters of the language.
+	pub language_coincidence: f64,
+	/// Frequency of each letter of the text, as given by `calculate_frequency`.
+	pub frequency: Vec<(char, f64)>,
+	/// The most repeated sequences of letters of the text.
+	pub repeats: Vec<Repeat>
+}
+
+impl Analizer {
+	/// Calculates the statistics of the given text, keeping the given amount
+	/// of its most repeated sequences of letters.
+	///
+	/// A monoalphabetic cypher keeps the entropy and the index of coincidence
+	/// of the language, while a polyalphabetic one flattens them, and the
+	/// spacings of its repeated sequences tend to be multiples of its key
+	/// length.
+	pub fn text_stats(&self, text: &str, top: usize) -> Result<TextStats, Error> {
+		let text_chars: Vec<char> = text.chars().collect();
+		let frequency = self.calculate_frequency(&text_chars)?;
+		let letters: Vec<char> = text_chars.iter().filter_map(|&c| self.alphabet.normalize(c)).collect();
+
+		let mut symbols: Vec<char> = text_chars.iter().copied().filter(|c| !c.is_whitespace()).collect();
+		symbols.sort_unstable();
+		symbols.dedup();
+
+		let n = letters.len() as f64;
+		let coincidence = match letters.len() {
+			0 | 1 => 0.0,
+			_ => frequency.iter().map(|(_, f)| (f * n) * (f * n - 1.0)).sum::<f64>() / (n * (n - 1.0))
+		};
+
+		let language: Vec<f64> = self.model.letters().iter().map(|(_, f)| *f).collect();
+		let language_total: f64 = language.iter().sum();
+		let language: Vec<f64> = language.iter().map(|f| f / language_total).collect();
+
+		let mut repeats = Vec::new();
+		for n in REPEAT_LENGTHS {
+			let Ok(grams) = self.calculate_ngram_frequency(&text_chars, n) else { break };
+			let total = letters.len() + 1 - n;
+			for (sequence, f) in grams {
+				let count = (f * total as f64).round() as usize;
+				if count < 2 { break; }
+				repeats.push(Repeat { spacings: spacings(&letters, &sequence), sequence, count });
+			}
+		}
+		// the longest sequences come first among the equally repeated ones,
+		// since they are less likely to repeat by chance
+		repeats.sort_by(|a, b| {
+			b.count
+				.cmp(&a.count)
+				.then(b.sequence.chars().count().cmp(&a.sequence.chars().count()))
+				.then(a.sequence.cmp(&b.sequence))
+		});
+		repeats.truncate(top);
+
+		Ok(TextStats {
+			length: letters.len(),
+			distinct_letters: frequency.len(),
+			distinct_symbols: symbols.len(),
+			entropy: entropy(frequency.iter().map(|(_, f)| *f)),
+			coincidence,
+			language_entropy: entropy(language.iter().copied()),
+			language_coincidence: language.iter().map(|f| f * f).sum(),
+			frequency,
+			repeats
+		})
+	}
+}
+
+/// Calculates the Shannon entropy, in bits, of the given probabilities.
+fn entropy<I: Iterator<Item = f64>>(probabilities: I) -> f64 {
+	probabilities.filter(|&p| p > 0.0).map(|p| -p * p.log2()).sum()
+}
+
+/// Retrieves the spacings between the consecutive occurrences of the given
+/// sequence inside the given letters.
+fn spacings(letters: &[char], sequence: &str) -> Vec<usize> {
+	let sequence: Vec<char> = sequence.chars().collect();
+	let positions: Vec<usize> = letters
+		.windows(sequence.len())
+		.enumerate()
+		.filter(|(_, w)| *w == sequence.as_slice())
+		.map(|(i, _)| i)
+		.collect();
+
+	positions.windows(2).map(|p| p[1] - p[0]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::model::LanguageModel;
+
+	#[test]
+	fn stats_are_calculated() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let stats = analizer.text_stats("ABCD abcd, ABCX ... 42", 3).unwrap();
+
+		assert_eq!(stats.length, 12);
+		assert_eq!(stats.distinct_letters, 5);
+		assert_eq!(stats.distinct_symbols, 13);
+		assert!((stats.coincidence - 20.0 / 132.0).abs() < 1e-9);
+		assert!(stats.language_entropy > 4.0 && stats.language_entropy < 4.7);
+		assert_eq!(stats.repeats[0], Repeat { sequence: "ABC".to_owned(), count: 3, spacings: vec![4, 4] });
+		assert_eq!(stats.repeats[1].sequence, "ABCDA");
+		assert_eq!(stats.repeats.len(), 3);
+	}
+
+	#[test]
+	fn entropy_is_measured_in_bits() {
+		assert_eq!(entropy([0.5, 0.5].into_iter()), 1.0);
+		assert_eq!(entropy([0.25; 4].into_iter()), 2.0);
+		assert_eq!(entropy([1.0, 0.0].into_iter()), 0.0);
+	}
+}
@@ -0,0 +1,224 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::error::Error;
+
+use super::{fitness::NgramFitness, key::SubstitutionKey, require_letters, Analizer, ALPHABET_LEN};
+
+/// This struct represents the best solution found by the substitution cracker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstitutionSolution {
+	/// The key that produced the solution.
+	pub key: SubstitutionKey,
+	/// The n-gram log-probability of the plaintext. The higher, the better.
+	pub score: f64,
+	/// The text resulting from applying the key over the cyphered text.
+	pub text: String
+}
+
+impl Analizer {
+	/// Searches the monoalphabetic substitution key that best decrypts the
+	/// given text.
+	///
+	/// The search starts from the key given by the frequency rank of the
+	/// letters, and then restarts from random keys as many times as specified,
+	/// improving each one of them by simulated annealing followed by
+	/// hill-climbing over the n-gram fitness of the language.
+	pub fn crack_substitution(&self, text: &str, restarts: usize) -> Result<SubstitutionSolution, Error> {
+		self.crack_substitution_with(text, restarts, &mut rand::thread_rng())
+	}
+
+	/// Same as [`Analizer::crack_substitution`], but drawing the random keys
+	/// from the given generator.
+	pub fn crack_substitution_with<R: Rng + ?Sized>(
+		&self,
+		text: &str,
+		restarts: usize,
+		rng: &mut R
+	) -> Result<SubstitutionSolution, Error>
+	{
+		self.complete_substitution_with(text, &SubstitutionKey::unknown(), restarts, rng)
+	}
+
+	/// Completes the given partial key into the substitution key that best
+	/// decrypts the given text, keeping the letters it knows fixed.
+	///
+	/// The search is the one of [`Analizer::crack_substitution`], but
+	/// every key it starts from holds the letters of the given one, and only
+	/// the plain letters of the unknown ones are ever exchanged.
+	pub fn complete_substitution_with<R: Rng + ?Sized>(
+		&self,
+		text: &str,
+		seed: &SubstitutionKey,
+		restarts: usize,
+		rng: &mut R
+	) -> Result<SubstitutionSolution, Error>
+	{
+		let fitness = NgramFitness::new(&self.model);
+		let cyphered = require_letters(text)?;
+
+		let free: Vec<usize> = (0..ALPHABET_LEN).filter(|&c| seed.get(c as u8).is_none()).collect();
+		let mut best_key = self.frequency_rank_key(&cyphered, seed);
+		let mut best_score = hill_climb(&fitness, &cyphered, &mut best_key, &free);
+		for _ in 0..restarts {
+			let mut key = anneal(&fitness, &cyphered, random_completion(seed, rng), &free, rng);
+			let score = hill_climb(&fitness, &cyphered, &mut key, &free);
+			if score > best_score {
+				best_key = key;
+				best_score = score;
+			}
+		}
+
+		Ok(SubstitutionSolution {
+			text: best_key.apply(text),
+			key: best_key,
+			score: best_score
+		})
+	}
+
+	/// Builds the key that maps the cyphered letters, from the most to the
+	/// least frequent one, to the letters of the language in the same order,
+	/// keeping the letters the given seed already knows.
+	fn frequency_rank_key(&self, cyphered: &[u8], seed: &SubstitutionKey) -> SubstitutionKey {
+		let mut counts = [0usize; ALPHABET_LEN];
+		for &c in cyphered { counts[c as usize] += 1; }
+
+		let mut by_rank: Vec<_> = (0..ALPHABET_LEN).filter(|&c| seed.get(c as u8).is_none()).collect();
+		by_rank.sort_by(|&a, &b| counts[b].cmp(&counts[a]).then(a.cmp(&b)));
+		let used = seed.inverse();
+
+		// the model may lack some letters of the alphabet, or have others
+		// outside of it, so the missing ones are appended at the end
+		let mut plain: Vec<u8> = self.model
+			.letters()
+			.iter()
+			.filter(|(c, _)| c.is_ascii_uppercase())
+			.map(|(c, _)| *c as u8 - b'A')
+			.collect();
+		for p in 0..ALPHABET_LEN as u8 {
+			if !plain.contains(&p) { plain.push(p); }
+		}
+		plain.retain(|&p| used.get(p).is_none());
+
+		let mut table = [0; ALPHABET_LEN];
+		for (c, p) in table.iter_mut().enumerate() {
+			*p = seed.get(c as u8).unwrap_or_default();
+		}
+		for (&c, &p) in by_rank.iter().zip(plain.iter()) {
+			table[c] = p;
+		}
+
+		SubstitutionKey::from_table(table).expect("rank keys are bijective")
+	}
+}
+
+/// Number of random exchanges tried by each annealing run.
+const ANNEALING_STEPS: usize = 20_000;
+/// Initial temperature of the annealing, in log-probability units per letter.
+const ANNEALING_TEMPERATURE: f64 = 0.05;
+
+/// Completes the given partial key by giving its unknown letters the
+/// remaining plain ones in a random order.
+fn random_completion<R: Rng + ?Sized>(seed: &SubstitutionKey, rng: &mut R) -> SubstitutionKey {
+	let used = seed.inverse();
+	let mut plain: Vec<u8> = (0..ALPHABET_LEN as u8).filter(|&p| used.get(p).is_none()).collect();
+	plain.shuffle(rng);
+
+	let mut plain = plain.into_iter();
+	let mut table = [0; ALPHABET_LEN];
+	for (c, p) in table.iter_mut().enumerate() {
+		*p = seed.get(c as u8).or_else(|| plain.next()).expect("every plain letter is left for the unknown ones");
+	}
+
+	SubstitutionKey::from_table(table).expect("completed keys are bijective")
+}
+
+/// Explores the keys around the given one by exchanging random pairs of the
+/// given letters, accepting worse keys with a probability that decreases as
+/// the search cools down, and returns the best key found along the way.
+fn anneal<R: Rng + ?Sized>(
+	fitness: &NgramFitness,
+	cyphered: &[u8],
+	mut key: SubstitutionKey,
+	free: &[usize],
+	rng: &mut R
+) -> SubstitutionKey
+{
+	if free.len() < 2 { return key; }
+
+	let mut plain = Vec::with_capacity(cyphered.len());
+	key.decrypt_indices(cyphered, &mut plain);
+	let mut current = fitness.score(&plain);
+	let mut best = (key.clone(), current);
+
+	let initial = ANNEALING_TEMPERATURE * cyphered.len().max(1) as f64;
+	for step in 0..ANNEALING_STEPS {
+		let temperature = initial * (1.0 - step as f64 / ANNEALING_STEPS as f64);
+		let (a, b) = (free[rng.gen_range(0..free.len())], free[rng.gen_range(0..free.len())]);
+		if a == b { continue; }
+
+		key.swap(a, b);
+		key.decrypt_indices(cyphered, &mut plain);
+		let score = fitness.score(&plain);
+		let delta = score - current;
+		if delta >= 0.0 || rng.gen::<f64>() < (delta / temperature).exp() {
+			current = score;
+			if current > best.1 { best = (key.clone(), current); }
+		} else {
+			key.swap(a, b);
+		}
+	}
+
+	best.0
+}
+
+/// Improves the given key by exchanging pairs of the given letters as long as
+/// any exchange increases the fitness of the plaintext, returning the final
+/// score.
+fn hill_climb(fitness: &NgramFitness, cyphered: &[u8], key: &mut SubstitutionKey, free: &[usize]) -> f64 {
+	let mut plain = Vec::with_capacity(cyphered.len());
+	key.decrypt_indices(cyphered, &mut plain);
+	let mut best = fitness.score(&plain);
+
+	let mut improved = true;
+	while improved {
+		improved = false;
+		for (i, &a) in free.iter().enumerate() {
+			for &b in free[i + 1..].iter() {
+				key.swap(a, b);
+				key.decrypt_indices(cyphered, &mut plain);
+				let score = fitness.score(&plain);
+				if score > best {
+					best = score;
+					improved = true;
+				} else {
+					key.swap(a, b);
+				}
+			}
+		}
+	}
+
+	best
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{rngs::StdRng, SeedableRng};
+
+	use super::*;
+	use super::super::model::LanguageModel;
+
+	#[test]
+	fn substitution_is_cracked() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let plain = "IN THE BEGINNING THE PROGRAM WAS WRITTEN TO READ A SINGLE LINE OF TEXT \
+			AND TO REPORT THE FREQUENCY OF EVERY LETTER FOUND INSIDE OF IT BUT THE USERS \
+			WANTED MORE THAN THAT SO THE AUTHORS DECIDED TO SEARCH FOR THE WHOLE KEY \
+			INSTEAD OF TRUSTING THE ORDER OF THE LETTERS AND THE RESULTS WERE MUCH BETTER";
+		let key = SubstitutionKey::random(&mut StdRng::seed_from_u64(7));
+		let cyphered = key.apply(plain);
+
+		let solution = analizer.crack_substitution_with(&cyphered, 3, &mut StdRng::seed_from_u64(42)).unwrap();
+
+		assert_eq!(solution.text, plain);
+	}
+}
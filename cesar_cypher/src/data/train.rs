@@ -0,0 +1,150 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::error::Error;
+
+use super::{fold_case, model::LanguageModel, strip_accent};
+
+/// This struct represents the counts of the letters, n-grams and words of a
+/// corpus, from which a new language model is built.
+///
+/// The corpus can be fed a text at a time, so it does not need to be loaded
+/// at once. The n-grams are counted over the letters of every text, skipping
+/// the rest of its characters, which is how the cyphered texts are scored.
+#[derive(Debug, Clone, Default)]
+pub struct Trainer {
+	strip_accents: bool,
+	letters: HashMap<char, usize>,
+	bigrams: HashMap<[char; 2], usize>,
+	quadgrams: HashMap<[char; 4], usize>,
+	words: HashMap<String, usize>
+}
+
+impl Trainer {
+	/// Creates a new trainer, which drops the diacritics of the letters of
+	/// the corpus when told so.
+	pub fn new(strip_accents: bool) -> Self {
+		Self { strip_accents, ..Self::default() }
+	}
+
+	/// Counts the letters, n-grams and words of the given text.
+	pub fn feed(&mut self, text: &str) {
+		let mut window: Vec<char> = Vec::with_capacity(4);
+		let mut word = String::new();
+		for c in text.chars() {
+			if !c.is_alphabetic() {
+				self.count_word(&mut word);
+				continue;
+			}
+
+			let c = match self.strip_accents {
+				true => strip_accent(fold_case(c)),
+				false => fold_case(c)
+			};
+			word.push(c);
+
+			if window.len() == 4 { window.remove(0); }
+			window.push(c);
+			*self.letters.entry(c).or_insert(0) += 1;
+			if let [.., a, b] = window[..] {
+				*self.bigrams.entry([a, b]).or_insert(0) += 1;
+			}
+			if let [a, b, c, d] = window[..] {
+				*self.quadgrams.entry([a, b, c, d]).or_insert(0) += 1;
+			}
+		}
+		self.count_word(&mut word);
+	}
+
+	fn count_word(&mut self, word: &mut String) {
+		if !word.is_empty() {
+			*self.words.entry(std::mem::take(word)).or_insert(0) += 1;
+		}
+	}
+
+	/// Builds the language model of the corpus fed so far, keeping only the
+	/// given amount of the most frequent quadgrams and words.
+	pub fn build(&self, name: &str, max_quadgrams: usize, max_words: usize) -> Result<LanguageModel, Error> {
+		if self.letters.is_empty() {
+			return Err(Error::EmptyInput);
+		}
+
+		LanguageModel::new(
+			name.to_string(),
+			percentages(&self.letters, usize::MAX),
+			percentages(&self.bigrams, usize::MAX)
+				.into_iter()
+				.map(|(g, f)| (g.iter().collect(), f))
+				.collect(),
+			percentages(&self.quadgrams, max_quadgrams)
+				.into_iter()
+				.map(|(g, f)| (g.iter().collect(), f))
+				.collect(),
+			percentages(&self.words, max_words)
+				.into_iter()
+				.map(|(w, _)| w)
+				.collect()
+		)
+	}
+}
+
+/// Turns the given counts into percentages of their total, sorted from the
+/// most to the least frequent entry and keeping only the given amount of them.
+fn percentages<K: Clone + Ord + Hash>(counts: &HashMap<K, usize>, max: usize) -> Vec<(K, f64)> {
+	let total: usize = counts.values().sum();
+	let mut entries: Vec<_> = counts.iter().collect();
+	entries.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+	entries
+		.into_iter()
+		.take(max)
+		.map(|(k, &n)| (k.clone(), n as f64 / total as f64 * 100.0))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn corpus_is_counted() {
+		let mut trainer = Trainer::new(false);
+		trainer.feed("That hat, that cat.");
+
+		let model = trainer.build("Tiny", 2, 1).unwrap();
+
+		assert_eq!(model.name(), "Tiny");
+		assert_eq!(model.letters()[0], ('T', 6.0 / 14.0 * 100.0));
+		assert_eq!(model.bigrams()[0], ("AT".to_string(), 4.0 / 13.0 * 100.0));
+		assert_eq!(model.quadgrams().len(), 2);
+		assert_eq!(model.quadgrams()[0], ("THAT".to_string(), 3.0 / 11.0 * 100.0));
+		assert_eq!(model.words(), &["THAT".to_string()]);
+	}
+
+	#[test]
+	fn accents_are_stripped() {
+		let mut trainer = Trainer::new(true);
+		trainer.feed("Él está aquí");
+
+		let model = trainer.build("", 10, 10).unwrap();
+
+		assert!(model.letters().iter().all(|(c, _)| c.is_ascii_uppercase()));
+		assert!(Trainer::new(false).build("", 10, 10).is_err());
+	}
+
+	#[test]
+	fn trained_model_is_written_and_parsed() {
+		let mut trainer = Trainer::new(false);
+		trainer.feed("the quick brown fox jumps over the lazy dog");
+		let model = trainer.build("Pangram", 100, 100).unwrap();
+
+		let parsed = LanguageModel::parse(&model.to_text()).unwrap();
+
+		assert_eq!(parsed.name(), "Pangram");
+		assert_eq!(parsed.words(), model.words());
+		for ((c, f), (pc, pf)) in model.letters().iter().zip(parsed.letters()) {
+			assert_eq!(c, pc);
+			assert!((f - pf).abs() < 1e-3);
+		}
+		assert_eq!(parsed.quadgrams().len(), model.quadgrams().len());
+	}
+}
@@ -0,0 +1,327 @@
+use std::f64::consts::LN_10;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::error::Error;
+
+use super::{fitness::NgramFitness, match_case, rank_candidates, require_letters, Analizer, Candidate};
+
+/// Largest amount of columns whose orders are all tried, instead of searched.
+const EXHAUSTIVE_COLUMNS: usize = 7;
+
+impl Analizer {
+	/// Tries every amount of rails of a rail-fence cypher up to the given
+	/// maximum, starting the zigzag at every possible offset, and ranks the
+	/// resulting plaintexts by their n-gram fitness per letter.
+	pub fn crack_rail_fence(&self, text: &str, max_rails: usize) -> Result<Vec<Candidate>, Error> {
+		let cyphered = require_letters(text)?;
+		let fitness = NgramFitness::new(&self.model);
+
+		let mut candidates = Vec::new();
+		for rails in 2..=max_rails.min(cyphered.len() / 2).max(2) {
+			for offset in 0..2 * (rails - 1) {
+				let order = rail_fence_order(cyphered.len(), rails, offset);
+				let key = format!("{rails} rails, offset {offset}");
+				candidates.push(transposition_candidate(&fitness, text, &untranspose(&cyphered, &order), key));
+			}
+		}
+
+		rank_candidates(&mut candidates, LN_10 * cyphered.len() as f64);
+
+		Ok(candidates)
+	}
+
+	/// Searches the order of the columns of a columnar transposition for
+	/// every key length up to the given maximum, and ranks the best
+	/// plaintext of each length by its n-gram fitness per letter.
+	///
+	/// The orders of the shortest keys are all tried, while the order of the
+	/// longer ones is searched by hill-climbing over the bigram fitness of
+	/// the plaintext, exchanging and moving its columns, starting from the
+	/// columns in their order and then from as many random orders as
+	/// specified.
+	pub fn crack_columnar(&self, text: &str, max_key_len: usize, restarts: usize) -> Result<Vec<Candidate>, Error> {
+		self.crack_columnar_with(text, max_key_len, restarts, &mut rand::thread_rng())
+	}
+
+	/// Same as [`Analizer::crack_columnar`], but drawing the random orders
+	/// from the given generator.
+	pub fn crack_columnar_with<R: Rng + ?Sized>(
+		&self,
+		text: &str,
+		max_key_len: usize,
+		restarts: usize,
+		rng: &mut R
+	) -> Result<Vec<Candidate>, Error>
+	{
+		let cyphered = require_letters(text)?;
+		let guide = NgramFitness::bigrams(&self.model);
+		let fitness = NgramFitness::new(&self.model);
+
+		let mut candidates = Vec::new();
+		for columns in 2..=max_key_len.min(cyphered.len() / 2).max(2) {
+			let best = match columns <= EXHAUSTIVE_COLUMNS {
+				true => try_columns(&guide, &cyphered, columns),
+				false => search_columns(&guide, &cyphered, columns, restarts, rng)
+			};
+
+			let plain = untranspose(&cyphered, &columnar_order(cyphered.len(), &best));
+			candidates.push(transposition_candidate(&fitness, text, &plain, columnar_key(&best)));
+		}
+
+		rank_candidates(&mut candidates, LN_10 * cyphered.len() as f64);
+
+		Ok(candidates)
+	}
+
+	/// Cracks the given text as both a rail-fence and a columnar transposition,
+	/// ranking the candidates of both solvers together.
+	pub fn crack_transposition(&self, text: &str, max_key_len: usize, restarts: usize) -> Result<Vec<Candidate>, Error> {
+		let mut candidates = self.crack_rail_fence(text, max_key_len)?;
+		candidates.extend(self.crack_columnar(text, max_key_len, restarts)?);
+
+		let len = require_letters(text)?.len();
+		rank_candidates(&mut candidates, LN_10 * len as f64);
+
+		Ok(candidates)
+	}
+}
+
+/// Retrieves the position inside the plaintext of every letter of a text
+/// cyphered by a rail-fence of the given amount of rails, whose zigzag starts
+/// at the given offset.
+fn rail_fence_order(len: usize, rails: usize, offset: usize) -> Vec<usize> {
+	let cycle = 2 * (rails - 1);
+	let rail = |i: usize| {
+		let step = (i + offset) % cycle;
+		step.min(cycle - step)
+	};
+
+	// the rails are read from top to bottom, each one from left to right
+	let mut order: Vec<usize> = (0..len).collect();
+	order.sort_by_key(|&i| rail(i));
+
+	order
+}
+
+/// Retrieves the position inside the plaintext of every letter of a text
+/// cyphered by a columnar transposition that reads the columns in the given
+/// order.
+fn columnar_order(len: usize, perm: &[usize]) -> Vec<usize> {
+	perm.iter().flat_map(|&c| (c..len).step_by(perm.len())).collect()
+}
+
+/// Writes the key of a columnar transposition as the turn every column is
+/// read at.
+fn columnar_key(perm: &[usize]) -> String {
+	let mut turns = vec![0; perm.len()];
+	for (turn, &c) in perm.iter().enumerate() {
+		turns[c] = turn + 1;
+	}
+
+	turns.iter().map(|t| t.to_string()).collect::<Vec<_>>().join("-")
+}
+
+/// Puts every cyphered letter back at its position inside the plaintext.
+fn untranspose(cyphered: &[u8], order: &[usize]) -> Vec<u8> {
+	let mut plain = vec![0; cyphered.len()];
+	for (&c, &i) in cyphered.iter().zip(order.iter()) {
+		plain[i] = c;
+	}
+
+	plain
+}
+
+/// Scores the plaintext given by the given order of the columns.
+fn score_columns(fitness: &NgramFitness, cyphered: &[u8], perm: &[usize]) -> f64 {
+	fitness.score(&untranspose(cyphered, &columnar_order(cyphered.len(), perm)))
+}
+
+/// Tries every order of the given amount of columns, returning the best one.
+fn try_columns(fitness: &NgramFitness, cyphered: &[u8], columns: usize) -> Vec<usize> {
+	let mut perm: Vec<usize> = (0..columns).collect();
+	let mut best = (perm.clone(), score_columns(fitness, cyphered, &perm));
+
+	// the orders are visited by Heap's algorithm, a single exchange apart
+	let mut counters = vec![0; columns];
+	let mut i = 1;
+	while i < columns {
+		if counters[i] < i {
+			perm.swap(if i % 2 == 0 { 0 } else { counters[i] }, i);
+			let score = score_columns(fitness, cyphered, &perm);
+			if score > best.1 { best = (perm.clone(), score); }
+			counters[i] += 1;
+			i = 1;
+		} else {
+			counters[i] = 0;
+			i += 1;
+		}
+	}
+
+	best.0
+}
+
+/// Searches the best order of the given amount of columns by hill-climbing
+/// from the columns in their order and from the given amount of random ones.
+fn search_columns<R: Rng + ?Sized>(
+	fitness: &NgramFitness,
+	cyphered: &[u8],
+	columns: usize,
+	restarts: usize,
+	rng: &mut R
+) -> Vec<usize>
+{
+	let mut best = (0..columns).collect::<Vec<_>>();
+	let mut best_score = climb_columns(fitness, cyphered, &mut best);
+	for _ in 0..restarts {
+		let mut perm = best.clone();
+		perm.shuffle(rng);
+		let score = climb_columns(fitness, cyphered, &mut perm);
+		if score > best_score {
+			best = perm;
+			best_score = score;
+		}
+	}
+
+	best
+}
+
+/// Improves the given order of the columns by exchanging, moving or rotating
+/// them as long as any change increases the fitness of the plaintext,
+/// returning the final score.
+///
+/// An order whose columns are all off by a same amount yields the plaintext
+/// off by a few letters, which scores almost as well as the right one while
+/// being far from it by exchanges, so every rotation is tried too.
+fn climb_columns(fitness: &NgramFitness, cyphered: &[u8], perm: &mut Vec<usize>) -> f64 {
+	let mut best = score_columns(fitness, cyphered, perm);
+
+	let mut improved = true;
+	while improved {
+		improved = false;
+		for a in 0..perm.len() {
+			for b in 0..perm.len() {
+				if a == b { continue; }
+
+				let mut next = perm.clone();
+				match a < b {
+					true => next.swap(a, b),
+					false => {
+						let column = next.remove(a);
+						next.insert(b, column);
+					}
+				}
+
+				let next_score = score_columns(fitness, cyphered, &next);
+				if next_score > best {
+					*perm = next;
+					best = next_score;
+					improved = true;
+				}
+			}
+		}
+
+		for k in 1..perm.len() {
+			let next: Vec<usize> = perm.iter().map(|&c| (c + k) % perm.len()).collect();
+			let next_score = score_columns(fitness, cyphered, &next);
+			if next_score > best {
+				*perm = next;
+				best = next_score;
+				improved = true;
+			}
+		}
+	}
+
+	best
+}
+
+/// Builds the candidate of the given plaintext, written over the letters of
+/// the cyphered text so its layout is kept, scored by its n-gram fitness per
+/// letter, negated so the lower, the better.
+fn transposition_candidate(fitness: &NgramFitness, text: &str, plain: &[u8], key: String) -> Candidate {
+	let mut letters = plain.iter();
+	let text = text
+		.chars()
+		.map(|c| match c.is_ascii_alphabetic() {
+			true => letters.next().map_or(c, |&p| match_case((b'A' + p) as char, c)),
+			false => c
+		})
+		.collect();
+
+	Candidate {
+		key,
+		text,
+		score: -fitness.score(plain) / plain.len().max(1) as f64,
+		confidence: 0.0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{rngs::StdRng, SeedableRng};
+
+	use super::*;
+	use super::super::{letter_indices, model::LanguageModel};
+
+	const PLAIN: &str = "IT IS A TRUTH UNIVERSALLY ACKNOWLEDGED THAT A SINGLE MAN IN POSSESSION OF A GOOD \
+		FORTUNE MUST BE IN WANT OF A WIFE HOWEVER LITTLE KNOWN THE FEELINGS OR VIEWS OF SUCH A MAN MAY BE";
+
+	/// Cyphers the letters of the given text, reading them in the given order.
+	fn transpose(text: &str, order: &[usize]) -> String {
+		let letters = letter_indices(text);
+		order.iter().map(|&i| (b'A' + letters[i]) as char).collect()
+	}
+
+	fn letters(text: &str) -> String {
+		text.chars().filter(char::is_ascii_alphabetic).collect()
+	}
+
+	#[test]
+	fn rail_fence_is_cracked() {
+		let plain = "WEAREDISCOVEREDFLEEATONCE";
+		assert_eq!(transpose(plain, &rail_fence_order(plain.len(), 3, 0)), "WECRLTEERDSOEEFEAOCAIVDEN");
+
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = transpose(PLAIN, &rail_fence_order(letters(PLAIN).len(), 4, 0));
+		let candidates = analizer.crack_rail_fence(&cyphered, 8).unwrap();
+
+		assert_eq!(candidates[0].text, letters(PLAIN));
+		assert_eq!(candidates[0].key, "4 rails, offset 0");
+	}
+
+	#[test]
+	fn columnar_is_cracked() {
+		let perm = [2, 0, 4, 1, 3];
+		assert_eq!(columnar_key(&perm), "2-4-1-5-3");
+
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = transpose(PLAIN, &columnar_order(letters(PLAIN).len(), &perm));
+		let candidates = analizer.crack_columnar_with(&cyphered, 7, 2, &mut StdRng::seed_from_u64(42)).unwrap();
+
+		assert_eq!(candidates[0].text, letters(PLAIN));
+		assert_eq!(candidates[0].key, "2-4-1-5-3");
+	}
+
+	#[test]
+	fn long_columnar_keys_are_searched() {
+		// too many columns to try every order, so they are climbed instead, over
+		// a text long enough to fill every column
+		let plain = format!("{PLAIN} ON HIS FIRST ENTERING A NEIGHBOURHOOD THIS TRUTH IS SO WELL FIXED IN THE MINDS \
+			OF THE SURROUNDING FAMILIES THAT HE IS CONSIDERED THE RIGHTFUL PROPERTY OF SOME ONE OR OTHER OF THEIR \
+			DAUGHTERS");
+		let perm = [3, 7, 0, 5, 8, 2, 6, 1, 4];
+		assert!(perm.len() > EXHAUSTIVE_COLUMNS);
+
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = transpose(&plain, &columnar_order(letters(&plain).len(), &perm));
+		let guide = NgramFitness::bigrams(analizer.get_model());
+		let found = search_columns(&guide, &letter_indices(&cyphered), perm.len(), 40, &mut StdRng::seed_from_u64(2));
+
+		assert_eq!(found, perm);
+
+		let candidates = analizer.crack_columnar_with(&cyphered, 9, 40, &mut StdRng::seed_from_u64(2)).unwrap();
+
+		assert_eq!(candidates[0].text, letters(&plain));
+		assert_eq!(candidates[0].key, columnar_key(&perm));
+	}
+}
@@ -0,0 +1,272 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::error::Error;
+
+use super::{chi_squared, require_letters, Analizer, ALPHABET_LEN};
+
+/// Lengths of the repeated sequences looked for by the Kasiski examination.
+const KASISKI_LENGTHS: [usize; 3] = [3, 4, 5];
+
+/// The polyalphabetic cyphers the `Analizer` is able to crack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+	/// Each letter is shifted forward by its key letter, `c = p + k`.
+	Vigenere,
+	/// Each letter is subtracted from its key letter, `c = k - p`.
+	Beaufort,
+	/// A Vigenère cypher whose key is made of digits, `c = p + d`.
+	Gronsfeld
+}
+
+impl Variant {
+	pub const VARIANTS: &'static [&'static str] = &["vigenere", "beaufort", "gronsfeld"];
+
+	/// Amount of distinct shifts a single key symbol may represent.
+	fn key_space(&self) -> u8 {
+		match self {
+			Self::Gronsfeld => 10,
+			_ => ALPHABET_LEN as u8
+		}
+	}
+
+	/// Decrypts a single letter, with both the letter and the key expressed
+	/// as their position inside the alphabet.
+	fn decrypt(&self, c: u8, k: u8) -> u8 {
+		let n = ALPHABET_LEN as u8;
+		match self {
+			Self::Vigenere | Self::Gronsfeld => (c + n - k) % n,
+			Self::Beaufort => (k + n - c) % n
+		}
+	}
+
+	/// Retrieves the printable symbol of the given key shift.
+	fn key_symbol(&self, k: u8) -> char {
+		match self {
+			Self::Gronsfeld => (b'0' + k) as char,
+			_ => (b'A' + k) as char
+		}
+	}
+}
+
+impl FromStr for Variant {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"vigenere" => Ok(Self::Vigenere),
+			"beaufort" => Ok(Self::Beaufort),
+			"gronsfeld" => Ok(Self::Gronsfeld),
+			unk => Err(format!(r#"unknown variant "{unk}""#))
+		}
+	}
+}
+
+/// This struct represents the statistics gathered for a possible key length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyLength {
+	/// The length of the key.
+	pub length: usize,
+	/// The average index of coincidence of the columns the text splits into.
+	pub coincidence: f64,
+	/// The fraction of the Kasiski spacings that are multiple of the length.
+	pub kasiski: f64,
+	/// The combination of both statistics used to rank the length.
+	pub score: f64
+}
+
+/// This struct represents the solution found by the polyalphabetic cracker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolyalphabeticSolution {
+	/// The variant of the cypher that was cracked.
+	pub variant: Variant,
+	/// The recovered keyword.
+	pub keyword: String,
+	/// The text resulting from applying the keyword over the cyphered text.
+	pub text: String,
+	/// The key lengths that were considered, from the most to the least likely.
+	pub key_lengths: Vec<KeyLength>
+}
+
+impl Analizer {
+	/// Cracks a polyalphabetic cypher of the given variant.
+	///
+	/// The length of the key is estimated by combining the Kasiski examination
+	/// of the repeated sequences with the index of coincidence of the columns
+	/// the text splits into. Then, every column is solved as a Caesar shift
+	/// against the language's frequency analysis.
+	pub fn crack_polyalphabetic(
+		&self,
+		text: &str,
+		variant: Variant,
+		max_key_len: usize
+	) -> Result<PolyalphabeticSolution, Error>
+	{
+		let cyphered = require_letters(text)?;
+		let key_lengths = self.rank_key_lengths(&cyphered, max_key_len);
+		let length = key_lengths.first().map_or(1, |k| k.length);
+
+		let expected = self.letters_table();
+		let key: Vec<_> = (0..length)
+			.map(|col| {
+				let column: Vec<_> = cyphered.iter().skip(col).step_by(length).copied().collect();
+				(0..variant.key_space())
+					.map(|k| {
+						let mut counts = [0; ALPHABET_LEN];
+						for &c in column.iter() { counts[variant.decrypt(c, k) as usize] += 1; }
+						(k, chi_squared(&counts, &expected))
+					})
+					.min_by(|a, b| a.1.total_cmp(&b.1))
+					.map_or(0, |(k, _)| k)
+			})
+			.collect();
+
+		Ok(PolyalphabeticSolution {
+			variant,
+			keyword: key.iter().map(|&k| variant.key_symbol(k)).collect(),
+			text: decrypt_polyalphabetic(text, &key, variant),
+			key_lengths
+		})
+	}
+
+	/// Ranks every key length up to the given maximum by how likely it is to
+	/// be the length of the key that cyphered the given letters.
+	pub(super) fn rank_key_lengths(&self, cyphered: &[u8], max_key_len: usize) -> Vec<KeyLength> {
+		let expected_ic: f64 = self.letters_table().iter().map(|p| p * p).sum();
+		let spacings = kasiski_spacings(cyphered);
+		let max_key_len = max_key_len.min(cyphered.len() / 2).max(1);
+
+		let mut lengths: Vec<_> = (1..=max_key_len)
+			.map(|length| {
+				let coincidence = (0..length)
+					.map(|col| {
+						let column: Vec<_> = cyphered.iter().skip(col).step_by(length).copied().collect();
+						index_of_coincidence(&column)
+					})
+					.sum::<f64>() / length as f64;
+				let kasiski = match spacings.len() {
+					0 => 0.0,
+					n => spacings.iter().filter(|&&s| s % length == 0).count() as f64 / n as f64
+				};
+
+				// the coincidence favours the multiples of the real length, while
+				// the Kasiski examination favours its divisors, so combining both
+				// of them peaks at the real one
+				let score = (coincidence / expected_ic) * (0.5 + kasiski);
+				KeyLength { length, coincidence, kasiski, score }
+			})
+			.collect();
+
+		lengths.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.length.cmp(&b.length)));
+		lengths
+	}
+}
+
+/// Decrypts the given text with the given key, leaving the characters that are
+/// not letters untouched and without consuming the key with them.
+fn decrypt_polyalphabetic(text: &str, key: &[u8], variant: Variant) -> String {
+	let mut key_iter = key.iter().cycle();
+	text
+		.chars()
+		.map(|c| {
+			let base = match c {
+				'A'..='Z' => b'A',
+				'a'..='z' => b'a',
+				_ => return c
+			};
+			let k = *key_iter.next().unwrap_or(&0);
+			(variant.decrypt(c as u8 - base, k) + base) as char
+		})
+		.collect()
+}
+
+/// Calculates the index of coincidence of the given letters, that is, the
+/// probability of two of them, picked at random, being the same letter.
+pub(crate) fn index_of_coincidence(letters: &[u8]) -> f64 {
+	let n = letters.len();
+	if n < 2 { return 0.0; }
+
+	let mut counts = [0usize; ALPHABET_LEN];
+	for &c in letters { counts[c as usize] += 1; }
+
+	counts.iter().map(|&c| (c * c.saturating_sub(1)) as f64).sum::<f64>() / (n * (n - 1)) as f64
+}
+
+/// Retrieves the spacings between every pair of consecutive occurrences of the
+/// sequences that repeat inside the given letters.
+fn kasiski_spacings(letters: &[u8]) -> Vec<usize> {
+	let mut spacings = Vec::new();
+	for n in KASISKI_LENGTHS {
+		let mut last_seen: HashMap<&[u8], usize> = HashMap::new();
+		for (i, seq) in letters.windows(n).enumerate() {
+			if let Some(prev) = last_seen.insert(seq, i) {
+				spacings.push(i - prev);
+			}
+		}
+	}
+
+	spacings
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::model::LanguageModel;
+
+	const PLAIN: &str = "THE ANALYSIS OF A POLYALPHABETIC CYPHER STARTS BY GUESSING THE LENGTH \
+		OF ITS KEY, WHICH CAN BE DONE BY LOOKING AT THE DISTANCE BETWEEN THE SEQUENCES \
+		THAT REPEAT ALONG THE TEXT AND AT THE INDEX OF COINCIDENCE OF EVERY COLUMN. \
+		ONCE THE LENGTH IS KNOWN, EACH ONE OF THE COLUMNS IS JUST A CAESAR CYPHER THAT \
+		CAN BE SOLVED BY COMPARING ITS FREQUENCIES AGAINST THE ONES OF THE LANGUAGE.";
+
+	fn encrypt(text: &str, key: &[u8], variant: Variant) -> String {
+		let n = ALPHABET_LEN as u8;
+		let mut key_iter = key.iter().cycle();
+		text
+			.chars()
+			.map(|c| match c {
+				'A'..='Z' => {
+					let (p, k) = (c as u8 - b'A', *key_iter.next().unwrap_or(&0));
+					let e = match variant {
+						Variant::Beaufort => (k + n - p) % n,
+						_ => (p + k) % n
+					};
+					(e + b'A') as char
+				},
+				_ => c
+			})
+			.collect()
+	}
+
+	#[test]
+	fn vigenere_is_cracked() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = encrypt(PLAIN, &[11, 4, 12, 14, 13], Variant::Vigenere);
+
+		let solution = analizer.crack_polyalphabetic(&cyphered, Variant::Vigenere, 12).unwrap();
+
+		assert_eq!(solution.keyword, "LEMON");
+		assert_eq!(solution.text, PLAIN);
+	}
+
+	#[test]
+	fn beaufort_is_cracked() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = encrypt(PLAIN, &[10, 4, 24], Variant::Beaufort);
+
+		let solution = analizer.crack_polyalphabetic(&cyphered, Variant::Beaufort, 12).unwrap();
+
+		assert_eq!(solution.keyword, "KEY");
+		assert_eq!(solution.text, PLAIN);
+	}
+
+	#[test]
+	fn gronsfeld_is_cracked() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let cyphered = encrypt(PLAIN, &[3, 1, 4, 1], Variant::Gronsfeld);
+
+		let solution = analizer.crack_polyalphabetic(&cyphered, Variant::Gronsfeld, 12).unwrap();
+
+		assert_eq!(solution.keyword, "3141");
+		assert_eq!(solution.text, PLAIN);
+	}
+}
@@ -0,0 +1,69 @@
+use std::{fmt, io};
+
+/// The errors the application can run into while handling the user input.
+#[derive(Debug)]
+pub enum Error {
+	/// The text holds no letter to work with.
+	EmptyInput,
+	/// The text has more distinct frequency levels than letters the language
+	/// has to be associated with.
+	AlphabetOverflow {
+		/// The amount of distinct frequency levels of the text.
+		levels: usize,
+		/// The amount of letters of the language.
+		letters: usize
+	},
+	/// A symbol that can not be handled where it was found.
+	UnsupportedSymbol(char),
+	/// A key that can not be used by the requested cypher.
+	InvalidKey(String),
+	/// A known word of the plain text that can not be placed inside the
+	/// cyphered one.
+	InvalidCrib(String),
+	/// A language model that could not be parsed.
+	InvalidModel(String),
+	/// No text was given to the program.
+	MissingInput,
+	/// The text of the interactive mode was to be read from the standard
+	/// input, which the mode needs for its commands.
+	InteractiveInput,
+	/// A file or a standard stream could not be read or written.
+	Io(io::Error)
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::EmptyInput => write!(f, "the text has no letters to analyze"),
+			Self::AlphabetOverflow { levels, letters } => write!(
+				f,
+				"the text has {levels} distinct letter frequencies, but the language only has {letters} letters"
+			),
+			Self::UnsupportedSymbol(c) => write!(f, "unsupported symbol {c:?}"),
+			Self::InvalidKey(reason) => write!(f, "invalid key. {reason}"),
+			Self::InvalidCrib(reason) => write!(f, "invalid crib. {reason}"),
+			Self::InvalidModel(reason) => write!(f, "invalid language model. {reason}"),
+			Self::MissingInput => write!(f, "missing argument. specify any valid value"),
+			Self::InteractiveInput => write!(
+				f,
+				"the interactive mode reads its commands from the standard input. give the text as arguments or through --input"
+			),
+			Self::Io(err) => write!(f, "{err}")
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Io(err) => Some(err),
+			_ => None
+		}
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(err: io::Error) -> Self {
+		Self::Io(err)
+	}
+}
@@ -1,79 +1,480 @@
-mod data;
-mod output;
+//! Cryptanalysis of classical cyphers.
+//!
+//! Besides the `cesar_cypher` binary, the crate can be embedded by other
+//! programs. An [`Analizer`] compares the cyphered texts against a
+//! [`LanguageModel`], and every solver is exposed through the [`Cracker`]
+//! trait, which gives back its candidates ranked from the most to the least
+//! likely:
+//!
+//! ```
+//! use cesar_cypher::{Analizer, Cracker, LanguageModel, ShiftCracker};
+//!
+//! let analizer = Analizer::new(LanguageModel::default());
+//! let candidates = ShiftCracker.crack(&analizer, "Wkh txlfn eurzq ira mxpsv ryhu wkh odcb grj").unwrap();
+//!
+//! assert_eq!(candidates[0].text, "The quick brown fox jumps over the lazy dog");
+//! ```
+//!
+//! The results can be formatted by a [`Logger`], or serialized after the types
+//! of [`output::report`], which are independent from any printing.
 
-use std::{env, io::{self, ErrorKind, Write}};
+mod args;
+pub mod cipher;
+pub mod data;
+pub mod error;
+pub mod output;
+mod repl;
 
-use data::Analizer;
-use output::Logger;
+use std::{fs::{self, File}, io::{self, IsTerminal, Read, Write}, path::Path};
 
+use args::{CipherArgs, CliArgs, Mode, SubCmds, TrainArgs};
+use data::{detect_language, CribKey, model::Language, Alphabet, CipherType, Trainer, WordPatterns};
+use error::Error;
+use repl::Session;
+use structopt::StructOpt;
+
+pub use data::{
+	model::LanguageModel, AffineCracker, Analizer, Candidate, ColumnarCracker, Cracker, FrequencyCracker,
+	HomophonicCracker, PolyalphabeticCracker, RailFenceCracker, ShiftCracker, SubstitutionCracker, SubstitutionKey
+};
+pub use output::{Logger, OutputFormat};
+
+/// The command line application, which reads its arguments and writes the
+/// report of the requested analysis.
 pub struct App {
-	analizer: Analizer,
 	logger: Logger
 }
 
+impl Default for App {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl App {
 	pub fn new() -> Self {
 		Self {
-			analizer: Analizer::new(),
 			logger: Logger::new()
 		}
 	}
 
 	/// Runs the application and performs the analysis with the given argument
-	pub fn run(&self) -> Result<(), io::Error> {
-		let arg = Self::get_arg()?;
-		let eng_freq = self.analizer.get_english_freqs();
-		let arg_chars: Vec<_> = arg.chars().collect();
-		let arg_freq = self.analizer.calculate_frequency(&arg_chars);
-		let arg_ass = self.analizer.associate_frequency(&arg, &arg_freq);
-		let result = self.analizer.assemble_result(&arg, &arg_ass);
-
-		io::stdout().lock().write_all(
-			self.logger.format_report(
-				&arg,
-				&result,
-				&eng_freq,
-				&arg_freq,
-				&arg_ass
-			).as_bytes()
-		)?;
+	pub fn run(&mut self) -> Result<(), Error> {
+		let args = Self::get_args();
+		self.logger = Logger::with_format(args.format);
+		let input = args.input.as_deref();
+		let report = match &args.cmds {
+			Some(SubCmds::Encrypt { cipher, text }) => {
+				self.cipher_report(cipher, &Self::read_text(text, input)?, true)?
+			},
+			Some(SubCmds::Decrypt { cipher, text }) => {
+				self.cipher_report(cipher, &Self::read_text(text, input)?, false)?
+			},
+			Some(SubCmds::Train(train)) => Self::train_model(train)?,
+			None => {
+				if args.mode == Mode::Interactive && Self::reads_stdin(&args.text, input) {
+					return Err(Error::InteractiveInput);
+				}
+				match Self::open_input(&args.text, input)? {
+					Some(reader) if Self::streams_input(&args) => self.streamed_report(&args, reader)?,
+					Some(reader) => self.analysis_report(&args, &Self::read_all(reader)?)?,
+					None => self.analysis_report(&args, &args.text.join(" "))?
+				}
+			}
+		};
+
+		Self::write_report(&report, args.output.as_deref())
+	}
+
+	/// Writes the given report to the output file when given, or else to the
+	/// standard output.
+	fn write_report(report: &str, output: Option<&Path>) -> Result<(), Error> {
+		match output {
+			Some(path) => fs::write(path, report)?,
+			None => io::stdout().lock().write_all(report.as_bytes())?
+		}
 
 		Ok(())
 	}
 
-	/// Retrieves the arguments passed to the program and ensures that
-	/// those are valid for the application.
-	fn get_arg() -> Result<String, io::Error> {
-		// using args_os to avoid a possible panic
-		let cmd_args: Vec<_> = env::args_os().collect();
-		// ensuring that the program got an argument
-		match cmd_args.len() {
-			2  => {
-				// getting the input argument into the form required by the program
-				Ok(cmd_args
-				   .get(1)
-				   .unwrap()
-				   .to_string_lossy()
-				   .trim()
-				   .to_uppercase()
-				   .to_string()
-				)
+	/// Performs the analysis selected through the arguments over the given
+	/// text and formats its report.
+	fn analysis_report(&self, args: &CliArgs, arg: &str) -> Result<String, Error> {
+		if !args.detect {
+			return self.mode_report(args, arg, Self::load_model(args)?);
+		}
+
+		let models = Self::detection_models(args)?;
+		let guesses = detect_language(arg, &models)?;
+		let report = self.mode_report(args, arg, guesses[0].model.clone())?;
+
+		Ok(self.logger.format_detection(&guesses, &report))
+	}
+
+	/// Performs the analysis of the mode selected through the arguments over
+	/// the given text, comparing it against the given model.
+	fn mode_report(&self, args: &CliArgs, arg: &str, model: LanguageModel) -> Result<String, Error> {
+		self.solver_report(args, arg, &Self::build_analizer(args, model), args.mode)
+	}
+
+	/// Builds the analizer that compares the texts against the given model,
+	/// keeping the accents of its letters when requested.
+	fn build_analizer(args: &CliArgs, model: LanguageModel) -> Analizer {
+		match args.keep_accents {
+			true => {
+				let alphabet = Alphabet::from_model(&model).strip_accents(false);
+				Analizer::with_alphabet(model, alphabet)
 			},
-			n => {
-				let err = if n == 1 {
-					io::Error::new(
-						ErrorKind::InvalidData,
-						"missing argument. specify any valid value"
-					)
-				} else {
-					io::Error::new(
-						ErrorKind::InvalidInput,
-						"too many arguments. check your input data"
-					)
-				};
+			false => Analizer::new(model)
+		}
+	}
+
+	/// Tells whether the analysis selected through the arguments can count
+	/// the text as it is read, instead of reading it whole beforehand.
+	fn streams_input(args: &CliArgs) -> bool {
+		args.mode == Mode::Frequency && args.crib.is_none() && !args.detect
+	}
+
+	/// Performs the rank-order frequency association over the text read from
+	/// the given reader, counting it as it is read, and formats its report.
+	///
+	/// The text is never held whole, so only its first characters are
+	/// decrypted and shown along with the frequency of the whole text.
+	fn streamed_report(&self, args: &CliArgs, reader: impl Read) -> Result<String, Error> {
+		let analizer = Self::build_analizer(args, Self::load_model(args)?);
+		let mut preview = Preview::new(reader);
+		let (arg_freq, letters) = analizer.stream_letters(&mut preview, args.parallel)?;
+		let arg_ass = analizer.associate_levels(letters, &arg_freq)?;
+		let key = data::association_key(&arg_ass)?;
+
+		self.format_frequency(&analizer, preview.text(), &arg_freq, &arg_ass, &key, args.top)
+	}
 
-				Err(err)
+	/// Performs the analysis of the given mode over the given text.
+	fn solver_report(&self, args: &CliArgs, arg: &str, analizer: &Analizer, mode: Mode) -> Result<String, Error> {
+		let restarts = args.restarts.unwrap_or_else(|| mode.default_restarts());
+		let report = match (mode, &args.crib) {
+			(Mode::Shift | Mode::Frequency | Mode::Substitution, Some(crib)) => {
+				let (key, restarts) = match mode {
+					Mode::Shift => (CribKey::Shift, None),
+					Mode::Substitution => (CribKey::Substitution, Some(restarts)),
+					_ => (CribKey::Substitution, None)
+				};
+				let placements = analizer.place_crib(arg, crib, key, restarts)?;
+				self.logger.format_cribs(arg, crib, key, &placements)
+			},
+			(Mode::Shift, _) => {
+				let candidates = analizer.crack_shift(arg)?;
+				self.logger.format_candidates(arg, &candidates, args.top)
+			},
+			(Mode::Frequency, _) => self.frequency_report(analizer, arg, args.top, args.parallel)?,
+			(Mode::Substitution, _) => {
+				let solution = analizer.crack_substitution(arg, restarts)?;
+				// the letters missing from the text are guessed by the search,
+				// so only the ones it holds tell the keyword
+				let keyword = match args.keyword {
+					true => SubstitutionKey::from_associations(&solution.key.text_associations(arg))?.recover_keyword(),
+					false => None
+				};
+				self.logger.format_substitution(arg, &solution, keyword.as_ref())
+			},
+			(Mode::Homophonic, _) => {
+				let solution = analizer.crack_homophonic(arg, restarts)?;
+				self.logger.format_homophonic(arg, &solution)
+			},
+			(Mode::Polyalphabetic, _) => {
+				let solution = analizer.crack_polyalphabetic(arg, args.variant, args.max_key_len)?;
+				self.logger.format_polyalphabetic(arg, &solution, args.top)
+			},
+			(Mode::Affine, _) => {
+				let candidates = analizer.crack_affine(arg)?;
+				self.logger.format_candidates(arg, &candidates, args.top)
+			},
+			(Mode::RailFence, _) => {
+				let candidates = analizer.crack_rail_fence(arg, args.max_key_len)?;
+				self.logger.format_candidates(arg, &candidates, args.top)
+			},
+			(Mode::Columnar, _) => {
+				let candidates = analizer.crack_columnar(arg, args.max_key_len, restarts)?;
+				self.logger.format_candidates(arg, &candidates, args.top)
+			},
+			(Mode::Transposition, _) => {
+				let candidates = analizer.crack_transposition(arg, args.max_key_len, restarts)?;
+				self.logger.format_candidates(arg, &candidates, args.top)
+			},
+			(Mode::Pattern, _) => {
+				let dictionary = match &args.wordlist {
+					Some(path) => WordPatterns::from_file(path)?,
+					None => WordPatterns::from_words(analizer.get_model().words())
+				};
+				let solution = analizer.crack_patterns(arg, &dictionary)?;
+				let keyword = args.keyword.then(|| solution.key.recover_keyword()).flatten();
+				let key = keyword.as_ref().map_or(&solution.key, |k| &k.key);
+				let result = analizer.assemble_result(arg, &key.text_associations(arg))?;
+				self.logger.format_pattern(arg, &result, &solution, keyword.as_ref())
+			},
+			(Mode::Interactive, _) => {
+				let mut session = Session::new(analizer, arg)?;
+				session.run(io::stdin().lock(), io::stderr().lock())?;
+				let text_ass = session.key().text_associations(arg);
+				self.format_frequency(analizer, arg, session.text_freq(), &text_ass, session.key(), args.top)?
+			},
+			(Mode::Stats, _) => {
+				let stats = analizer.text_stats(arg, args.top)?;
+				self.logger.format_stats(analizer.get_model().name(), arg, &stats, analizer.get_language_freqs())
+			},
+			(Mode::Identify, _) => {
+				let identification = analizer.identify_cipher(arg, args.max_key_len)?;
+				let solver = match args.solve {
+					true => Self::solver_mode(identification.guesses[0].cipher),
+					false => None
+				};
+				let analysis = solver
+					.map(|mode| self.solver_report(args, arg, analizer, mode))
+					.transpose()?;
+				self.logger.format_identification(arg, &identification, analysis.as_deref())
 			}
+		};
+
+		Ok(report)
+	}
+
+	/// Transforms the given text with the cypher and key selected through the
+	/// arguments and formats its report.
+	fn cipher_report(&self, args: &CipherArgs, text: &str, encrypt: bool) -> Result<String, Error> {
+		let cipher = args.cipher.build(args.key.as_deref())?;
+		let result = match encrypt {
+			true => cipher.encrypt(text),
+			false => cipher.decrypt(text)
+		};
+
+		Ok(self.logger.format_transformation(text, &result))
+	}
+
+	/// Builds a language model out of the corpus files given through the
+	/// arguments and writes it in its textual representation.
+	fn train_model(args: &TrainArgs) -> Result<String, Error> {
+		let mut trainer = Trainer::new(args.strip_accents);
+		for path in args.corpus.iter() {
+			trainer.feed(&fs::read_to_string(path)?);
+		}
+
+		let name = args.name.as_deref().unwrap_or_default();
+		let model = trainer.build(name, args.max_quadgrams, args.max_words)?;
+
+		Ok(model.to_text())
+	}
+
+	/// Performs the rank-order frequency association over the given text
+	/// and formats its report.
+	fn frequency_report(
+		&self,
+		analizer: &Analizer,
+		arg: &str,
+		top: usize,
+		parallel: bool
+	) -> Result<String, Error> {
+		let arg_freq = analizer.stream_frequency(arg.as_bytes(), parallel)?;
+		let arg_ass = analizer.associate_frequency(arg, &arg_freq)?;
+		let key = analizer.frequency_key(arg, &arg_freq)?;
+
+		self.format_frequency(analizer, arg, &arg_freq, &arg_ass, &key, top)
+	}
+
+	/// Formats the report of the given association between the letters of the
+	/// text and the language's ones, ranking the decryptions the given key
+	/// is completed into.
+	///
+	/// The keys only cover the latin alphabet, so the texts written in other
+	/// scripts are reported without any ranked decryption.
+	fn format_frequency(
+		&self,
+		analizer: &Analizer,
+		arg: &str,
+		arg_freq: &[(char, f64)],
+		arg_ass: &[(char, char)],
+		key: &SubstitutionKey,
+		top: usize
+	) -> Result<String, Error> {
+		let result = analizer.assemble_result(arg, arg_ass)?;
+		let candidates = match analizer.rank_decryptions(arg, key, top) {
+			Err(Error::EmptyInput) => Vec::new(),
+			candidates => candidates?
+		};
+
+		Ok(self.logger.format_report(
+			analizer.get_model().name(),
+			arg,
+			&result,
+			analizer.get_language_freqs(),
+			arg_freq,
+			arg_ass,
+			&candidates
+		))
+	}
+
+	/// Retrieves the mode that solves the given type of cypher, if any.
+	fn solver_mode(cipher: CipherType) -> Option<Mode> {
+		match cipher {
+			CipherType::Monoalphabetic => Some(Mode::Substitution),
+			CipherType::Polyalphabetic => Some(Mode::Polyalphabetic),
+			CipherType::Transposition => Some(Mode::Transposition),
+			CipherType::Encoding => None
+		}
+	}
+
+	/// Loads the language model requested through the arguments, preferring
+	/// a model file over the bundled ones.
+	fn load_model(args: &CliArgs) -> Result<LanguageModel, Error> {
+		match &args.model {
+			Some(path) => LanguageModel::from_file(path),
+			None => Ok(LanguageModel::bundled(args.language))
+		}
+	}
+
+	/// Loads every language model the language of the text is detected among,
+	/// which are the bundled ones along with the model file, when given.
+	fn detection_models(args: &CliArgs) -> Result<Vec<LanguageModel>, Error> {
+		let mut models: Vec<_> = Language::ALL.into_iter().map(LanguageModel::bundled).collect();
+		if let Some(path) = &args.model {
+			models.push(LanguageModel::from_file(path)?);
+		}
+
+		Ok(models)
+	}
+
+	/// Retrieves the text to work with, which is read from the input file when
+	/// given, or joined from the text arguments, or else read from the piped
+	/// standard input.
+	fn read_text(text: &[String], input: Option<&Path>) -> Result<String, Error> {
+		match Self::open_input(text, input)? {
+			Some(reader) => Self::read_all(reader),
+			None => Ok(text.join(" "))
+		}
+	}
+
+	/// Opens the reader of the text to work with, which is the input file when
+	/// given, or else the piped standard input. No reader is opened when the
+	/// text is given through the arguments.
+	fn open_input(text: &[String], input: Option<&Path>) -> Result<Option<Box<dyn Read>>, Error> {
+		match input {
+			Some(path) if path == Path::new("-") => Ok(Some(Box::new(io::stdin().lock()))),
+			Some(path) => Ok(Some(Box::new(File::open(path)?))),
+			None if !text.is_empty() => Ok(None),
+			None if !io::stdin().is_terminal() => Ok(Some(Box::new(io::stdin().lock()))),
+			None => Err(Error::MissingInput)
+		}
+	}
+
+	/// Reads the whole text from the given reader.
+	fn read_all(mut reader: impl Read) -> Result<String, Error> {
+		let mut buf = String::new();
+		reader.read_to_string(&mut buf)?;
+
+		Ok(buf)
+	}
+
+	/// Tells whether the text would be read from the standard input, rather
+	/// than taken from the arguments or a file.
+	fn reads_stdin(text: &[String], input: Option<&Path>) -> bool {
+		match input {
+			Some(path) => path == Path::new("-"),
+			None => text.is_empty()
+		}
+	}
+
+	/// Retrieves the arguments passed to the program and ensures that
+	/// those are valid for the application.
+	fn get_args() -> CliArgs {
+		CliArgs::from_args()
+	}
+}
+
+/// Number of bytes kept from the start of a streamed text.
+const PREVIEW_LEN: usize = 4096;
+
+/// This struct represents a reader that keeps the first bytes of the text it
+/// reads, so a streamed text can still be partially shown.
+struct Preview<R> {
+	reader: R,
+	head: Vec<u8>
+}
+
+impl<R: Read> Preview<R> {
+	fn new(reader: R) -> Self {
+		Self { reader, head: Vec::with_capacity(PREVIEW_LEN) }
+	}
+
+	/// Retrieves the kept start of the text, cut at its last whole character.
+	fn text(&self) -> &str {
+		match std::str::from_utf8(&self.head) {
+			Ok(text) => text,
+			Err(e) => std::str::from_utf8(&self.head[..e.valid_up_to()]).expect("the prefix is valid")
 		}
 	}
 }
+
+impl<R: Read> Read for Preview<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let read = self.reader.read(buf)?;
+		let kept = read.min(PREVIEW_LEN - self.head.len());
+		self.head.extend_from_slice(&buf[..kept]);
+
+		Ok(read)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Builds a path inside the temporary directory that is unique to the
+	/// given test.
+	fn temp_path(name: &str) -> std::path::PathBuf {
+		std::env::temp_dir().join(format!("cesar_cypher_{name}_{}", std::process::id()))
+	}
+
+	#[test]
+	fn text_is_read_from_the_input_file() {
+		let path = temp_path("input");
+		fs::write(&path, "Wkh txlfn eurzq ira\n").unwrap();
+
+		// the input file takes precedence over the text arguments
+		let text = App::read_text(&["ignored".to_owned()], Some(&path));
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(text.unwrap(), "Wkh txlfn eurzq ira\n");
+		assert!(matches!(App::read_text(&[], Some(&temp_path("missing"))), Err(Error::Io(_))));
+	}
+
+	#[test]
+	fn text_arguments_are_joined() {
+		let text = App::read_text(&["Wkh".to_owned(), "txlfn".to_owned(), "eurzq".to_owned()], None).unwrap();
+
+		assert_eq!(text, "Wkh txlfn eurzq");
+		assert!(App::reads_stdin(&[], None));
+		assert!(App::reads_stdin(&["Wkh".to_owned()], Some(Path::new("-"))));
+		assert!(!App::reads_stdin(&["Wkh".to_owned()], None));
+	}
+
+	#[test]
+	fn streamed_text_is_previewed() {
+		let text = format!("a{}", "ñ".repeat(PREVIEW_LEN));
+		let mut preview = Preview::new(text.as_bytes());
+
+		assert_eq!(App::read_all(&mut preview).unwrap(), text);
+		// every «ñ» takes two bytes, so the last kept one is cut in half
+		assert_eq!(preview.text(), format!("a{}", "ñ".repeat(PREVIEW_LEN / 2 - 1)));
+	}
+
+	#[test]
+	fn report_is_written_to_the_output_file() {
+		let path = temp_path("output");
+
+		App::write_report("@> Report <@\n", Some(&path)).unwrap();
+		let written = fs::read_to_string(&path);
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(written.unwrap(), "@> Report <@\n");
+	}
+}
@@ -1,8 +1,7 @@
-use std::io;
-use cesar_cypher::App;
+use cesar_cypher::{error::Error, App};
 
-fn main() -> Result<(), io::Error>{
-	let app = App::new();
+fn main() -> Result<(), Error>{
+	let mut app = App::new();
 	app.run()?;
 
 	Ok(())
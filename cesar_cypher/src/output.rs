@@ -1,31 +1,98 @@
-use std::fmt::Display;
+//! The formatting of the results of the analysis, either as human readable
+//! tables or serialized after the report types of [`report`].
 
+pub mod report;
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::Serialize;
+
+use crate::data::{
+	Candidate, CribKey, CribPlacement, HomophonicSolution, Identification, KeywordKey, LanguageGuess,
+	PatternSolution, PolyalphabeticSolution, SubstitutionSolution, TextStats
+};
+use report::{
+	CandidatesReport, CribReport, DetectionReport, FrequencyReport, HomophonicReport, IdentificationReport,
+	PatternReport, PolyalphabeticReport, Rows, StatsReport, SubstitutionReport, TransformationReport
+};
+
+/// The formats the reports can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+	/// Human readable tables.
+	Table,
+	/// A JSON document per report.
+	Json,
+	/// `section,key,value` rows, with a header.
+	Csv
+}
+
+impl OutputFormat {
+	pub const VARIANTS: &'static [&'static str] = &["table", "json", "csv"];
+}
+
+impl FromStr for OutputFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"table" => Ok(Self::Table),
+			"json" => Ok(Self::Json),
+			"csv" => Ok(Self::Csv),
+			unk => Err(format!(r#"unknown format "{unk}""#))
+		}
+	}
+}
+
+/// This struct represents the formatter of the results of the analysis, which
+/// writes them in the format it was built with.
 #[derive(Debug)]
-pub struct Logger;
+pub struct Logger {
+	format: OutputFormat
+}
+
+impl Default for Logger {
+	fn default() -> Self {
+		Self::new()
+	}
+}
 
 impl Logger {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self { Self::with_format(OutputFormat::Table) }
 
+	pub fn with_format(format: OutputFormat) -> Self {
+		Self { format }
+	}
+
+	#[allow(clippy::too_many_arguments)]
 	pub fn format_report(
 		&self,
+		language: &str,
 		text: &str,
 		result: &str,
 		eng_freq: &[(char, f64)],
-		text_freq: &[(&char, f64)],
-		text_ass: &[(&char, &char)]
+		text_freq: &[(char, f64)],
+		text_ass: &[(char, char)],
+		candidates: &[Candidate]
 	) -> String {
+		let report = FrequencyReport::new(language, text, result, eng_freq, text_freq, text_ass, candidates);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
 		let mut template = String::new();
 		let eng_freq_fmt = Self::_format_pairs(eng_freq);
 		let text_freq_fmt = Self::_format_pairs(text_freq);
 		let text_ass_fmt = Self::_format_pairs(text_ass);
-		let posibilities = Self::_format_multi_posibilities(result, eng_freq);
+		let ranked_fmt = Self::_format_ranked_table(candidates);
+		let ranked_texts_fmt = Self::_format_ranked_texts(candidates);
 
 		template.push_str(&format!("
  +---------------------------+
 <| [Results of the Analysis] |>
  +---------------------------+
 
-@> English Frequency Analysis <@
+@> {language} Frequency Analysis <@
 +-------------+-------------+
 |  Character  |  Frequency  |
 +-------------+-------------+
@@ -49,13 +116,481 @@ impl Logger {
 @> Original and Resulting Texts <@
 [Original]: {text}
 [Result]:   {result}
-[Unknown characters posibilities]:
-{posibilities}
+
+@> Ranked Decryptions <@
++--------+-------------+-------------+
+|  Rank  |   Fitness   |  Confidence |
++--------+-------------+-------------+
+{ranked_fmt}
++--------+-------------+-------------+
+
+{ranked_texts_fmt}
 	"));
 		
 		template
 	}
 
+	pub fn format_candidates(
+		&self,
+		text: &str,
+		candidates: &[Candidate],
+		top: usize
+	) -> String {
+		let candidates = &candidates[..top.min(candidates.len())];
+		let report = CandidatesReport::new(text, candidates);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
+		let mut template = String::new();
+		let table_fmt = Self::_format_candidates_table(candidates);
+		let texts_fmt = Self::_format_candidates_texts(candidates);
+
+		template.push_str(&format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+@> Ranked Candidates <@
++--------+-------------+-------------+-------------+
+|  Rank  |     Key     |    Score    |  Confidence |
++--------+-------------+-------------+-------------+
+{table_fmt}
++--------+-------------+-------------+-------------+
+
+@> Original and Candidate Texts <@
+[Original]: {text}
+{texts_fmt}
+	"));
+
+		template
+	}
+
+	/// Formats the report of every placement of the crib inside the text that
+	/// is consistent with the given kind of key.
+	pub fn format_cribs(
+		&self,
+		text: &str,
+		crib: &str,
+		key: CribKey,
+		placements: &[CribPlacement]
+	) -> String {
+		let report = CribReport::new(text, crib, key, placements);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
+		if placements.is_empty() {
+			return format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+The crib \"{crib}\" fits nowhere in the text with a {key} key
+	");
+		}
+
+		let mut template = String::new();
+		let table_fmt = Self::_format_placements_table(placements);
+		let texts_fmt = Self::_format_placements_texts(placements);
+
+		template.push_str(&format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+@> Consistent Placements of \"{crib}\" ({key} key) <@
++--------+------------+-------------+-------------+-------------+
+|  Rank  |  Position  |     Key     |    Score    |  Confidence |
++--------+------------+-------------+-------------+-------------+
+{table_fmt}
++--------+------------+-------------+-------------+-------------+
+
+@> Original and Placement Texts <@
+[Original]: {text}
+{texts_fmt}
+	"));
+
+		template
+	}
+
+	pub fn format_stats(
+		&self,
+		language: &str,
+		text: &str,
+		stats: &TextStats,
+		lang_freq: &[(char, f64)]
+	) -> String {
+		let report = StatsReport::new(language, text, stats, lang_freq);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
+		let mut template = String::new();
+		let stats_fmt = Self::_format_stats_table(stats, lang_freq.len());
+		let repeats_fmt = Self::_format_repeats(stats);
+		let histogram_fmt = Self::_format_histogram(&stats.frequency, lang_freq);
+
+		template.push_str(&format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+@> Text Statistics <@
++------------------------+-------------+-------------+
+|        Statistic       |    Input    | {language:^11} |
++------------------------+-------------+-------------+
+{stats_fmt}
++------------------------+-------------+-------------+
+
+@> Repeated Sequences <@
+{repeats_fmt}
+
+@> Frequency Histogram <@
+{histogram_fmt}
+
+[Original]: {text}
+	"));
+
+		template
+	}
+
+	/// Formats the report of the detected language of the text, followed by
+	/// the given report of the analysis performed with it.
+	pub fn format_detection(&self, guesses: &[LanguageGuess], analysis: &str) -> String {
+		let mut report = DetectionReport::new(guesses);
+		report.analysis = self._analysis_value(Some(analysis));
+		if let Some(serialized) = self._serialize(&report) {
+			return self._append_analysis(serialized, Some(analysis));
+		}
+
+		let mut template = String::new();
+		let table_fmt = Self::_format_detection_table(guesses);
+		let (language, confidence) = guesses
+			.first()
+			.map_or(("", 0.0), |g| (g.model.name(), g.confidence * 100.0));
+
+		template.push_str(&format!("
+@> Language Detection <@
++--------+-------------+-------------+-------------+
+|  Rank  |   Language  |   Distance  |  Confidence |
++--------+-------------+-------------+-------------+
+{table_fmt}
++--------+-------------+-------------+-------------+
+
+[Detected]: {language} ({confidence:.2}%)
+{analysis}"));
+
+		template
+	}
+
+	/// Formats the report of the identified cypher of the text, followed by
+	/// the report of its solver, when it was dispatched to it.
+	pub fn format_identification(
+		&self,
+		text: &str,
+		identification: &Identification,
+		analysis: Option<&str>
+	) -> String {
+		let features = &identification.features;
+		let mut report = IdentificationReport::new(text, identification);
+		report.analysis = self._analysis_value(analysis);
+		if let Some(serialized) = self._serialize(&report) {
+			return self._append_analysis(serialized, analysis);
+		}
+
+		let mut template = String::new();
+		let table_fmt = Self::_format_identification_table(identification);
+		let encoding = features.encoding.unwrap_or("none");
+		let (cipher, confidence) = identification
+			.guesses
+			.first()
+			.map_or((String::new(), 0.0), |g| (g.cipher.to_string(), g.confidence * 100.0));
+
+		template.push_str(&format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+@> Cypher Features <@
+[Letter share]:         {:.4}
+[Encoding]:             {encoding}
+[Frequency distance]:   {:.4}
+[Profile distance]:     {:.4}
+[Coincidence]:          {:.4}
+[Period]:               {} (coincidence {:.4})
+
+@> Cypher Identification <@
++--------+----------------+-------------+-------------+
+|  Rank  |     Cypher     |   Distance  |  Confidence |
++--------+----------------+-------------+-------------+
+{table_fmt}
++--------+----------------+-------------+-------------+
+
+[Original]:   {text}
+[Identified]: {cipher} ({confidence:.2}%)
+{}",
+			features.letter_share,
+			features.frequency_distance,
+			features.profile_distance,
+			features.coincidence,
+			features.period,
+			features.period_coincidence,
+			analysis.unwrap_or_default()
+		));
+
+		template
+	}
+
+	pub fn format_substitution(
+		&self,
+		text: &str,
+		solution: &SubstitutionSolution,
+		keyword: Option<&KeywordKey>
+	) -> String {
+		let report = SubstitutionReport::new(text, solution, keyword);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
+		let mut template = String::new();
+		let key = &solution.key;
+		let score = solution.score;
+		let result = &solution.text;
+		let keyword_fmt = Self::_format_keyword(keyword);
+
+		template.push_str(&format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+@> Substitution Key <@
+[Cyphered]: ABCDEFGHIJKLMNOPQRSTUVWXYZ
+[Plain]:    {key}
+[Fitness]:  {score:.4}
+{keyword_fmt}
+@> Original and Resulting Texts <@
+[Original]: {text}
+[Result]:   {result}
+	"));
+
+		template
+	}
+
+	pub fn format_polyalphabetic(
+		&self,
+		text: &str,
+		solution: &PolyalphabeticSolution,
+		top: usize
+	) -> String {
+		let report = PolyalphabeticReport::new(text, solution, top);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
+		let mut template = String::new();
+		let variant = format!("{:?}", solution.variant);
+		let keyword = &solution.keyword;
+		let result = &solution.text;
+		let lengths_fmt = solution.key_lengths
+			.iter()
+			.take(top)
+			.map(|k| {
+				format!(
+					"| {:^11} | {:^11.4} | {:^11.4} | {:^11.4} |",
+					k.length, k.coincidence, k.kasiski, k.score
+				)
+			})
+			.collect::<Vec<String>>()
+			.join("\n");
+
+		template.push_str(&format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+@> Key Length Estimation <@
++-------------+-------------+-------------+-------------+
+|    Length   |      IC     |   Kasiski   |    Score    |
++-------------+-------------+-------------+-------------+
+{lengths_fmt}
++-------------+-------------+-------------+-------------+
+
+@> Recovered Key <@
+[Variant]: {variant}
+[Keyword]: {keyword}
+
+@> Original and Resulting Texts <@
+[Original]: {text}
+[Result]:   {result}
+	"));
+
+		template
+	}
+
+	pub fn format_homophonic(&self, text: &str, solution: &HomophonicSolution) -> String {
+		let report = HomophonicReport::new(text, solution);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
+		let mut template = String::new();
+		let homophones_fmt = Self::_format_homophones(solution);
+		let score = solution.score;
+		let result = &solution.text;
+
+		template.push_str(&format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+@> Homophones <@
++-------------+---------------------------------------+
+|    Letter   |                Symbols                |
++-------------+---------------------------------------+
+{homophones_fmt}
++-------------+---------------------------------------+
+[Symbols]: {}
+[Fitness]: {score:.4}
+
+@> Original and Resulting Texts <@
+[Original]: {text}
+[Result]:   {result}
+	", solution.symbols.len()));
+
+		template
+	}
+
+	pub fn format_pattern(
+		&self,
+		text: &str,
+		result: &str,
+		solution: &PatternSolution,
+		keyword: Option<&KeywordKey>
+	) -> String {
+		let report = PatternReport::new(text, result, solution, keyword);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
+		let mut template = String::new();
+		let text_ass_fmt = Self::_format_pairs(&solution.key.text_associations(text));
+		let key = &solution.key;
+		let matched = solution.matched_words;
+		let total = solution.total_words;
+		let keyword_fmt = Self::_format_keyword(keyword);
+
+		template.push_str(&format!("
+ +---------------------------+
+<| [Results of the Analysis] |>
+ +---------------------------+
+
+@> Input Associations <@
++-------------+-------------+
+|     From    |      To     |
++-------------+-------------+
+{text_ass_fmt}
++-------------+-------------+
+{keyword_fmt}
+@> Original and Resulting Texts <@
+[Key]:            {key}
+[Resolved words]: {matched} of {total}
+[Original]: {text}
+[Result]:   {result}
+	"));
+
+		template
+	}
+
+	pub fn format_transformation(&self, text: &str, result: &str) -> String {
+		let report = TransformationReport::new(text, result);
+		if let Some(serialized) = self._serialize(&report) {
+			return serialized;
+		}
+
+		let mut template = String::new();
+
+		template.push_str(&format!("
+ +---------------------------------+
+<| [Results of the Transformation] |>
+ +---------------------------------+
+
+[Original]: {text}
+[Result]:   {result}
+	"));
+
+		template
+	}
+
+	fn _format_candidates_table(candidates: &[Candidate]) -> String {
+		candidates
+			.iter()
+			.enumerate()
+			.map(|(i, c)| {
+				format!(
+					"| {:^6} | {:^11} | {:^11.4} | {:^11.4} |",
+					i + 1, c.key, c.score, c.confidence
+				)
+			})
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	fn _format_placements_table(placements: &[CribPlacement]) -> String {
+		placements
+			.iter()
+			.enumerate()
+			.map(|(i, p)| {
+				let c = &p.candidate;
+				format!(
+					"| {:^6} | {:^10} | {:^11} | {:^11.4} | {:^11.4} |",
+					i + 1, p.position, c.key, c.score, c.confidence
+				)
+			})
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	fn _format_placements_texts(placements: &[CribPlacement]) -> String {
+		placements
+			.iter()
+			.enumerate()
+			.map(|(i, p)| {
+				let mappings: Vec<_> = p.mappings.associations().iter().map(|(c, q)| format!("{c}={q}")).collect();
+				format!("[#{}]: {}\n      [Mappings]: {}", i + 1, p.candidate.text, mappings.join(" "))
+			})
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	fn _format_homophones(solution: &HomophonicSolution) -> String {
+		solution
+			.homophones()
+			.iter()
+			.map(|(p, symbols)| format!("| {:^11} | {:<37} |", p, symbols.join(" ")))
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	fn _format_keyword(keyword: Option<&KeywordKey>) -> String {
+		match keyword {
+			Some(k) => format!(
+				"\n@> Keyword Alphabet <@\n[Keyword]: {}\n[Shift]:   {}\n[Key]:     {}\n[Filled]:  {} letters\n",
+				k.keyword, k.shift, k.key, k.filled
+			),
+			None => String::new()
+		}
+	}
+
+	fn _format_candidates_texts(candidates: &[Candidate]) -> String {
+		candidates
+			.iter()
+			.enumerate()
+			.map(|(i, c)| format!("[#{}]: {}", i + 1, c.text))
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
 	fn _format_pairs<T, U>(result: &[(T, U)]) -> String
 		where
 			T: Display,
@@ -71,25 +606,197 @@ impl Logger {
 			.to_owned()
 	}
 
-	fn _format_multi_posibilities(text: &str, alphabet: &[(char, f64)]) -> String {
-		let mut template = String::new();
+	fn _format_ranked_table(candidates: &[Candidate]) -> String {
+		candidates
+			.iter()
+			.enumerate()
+			.map(|(i, c)| format!("| {:^6} | {:^11.4} | {:^11.4} |", i + 1, -c.score, c.confidence))
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
 
-		if !text.contains('?') {
-			template.push_str("There is not unknown characters");
-			return template;
+	fn _format_ranked_texts(candidates: &[Candidate]) -> String {
+		candidates
+			.iter()
+			.enumerate()
+			.map(|(i, c)| format!("[#{}]: {}\n      [Key]: {}", i + 1, c.text, c.key))
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	fn _format_stats_table(stats: &TextStats, lang_letters: usize) -> String {
+		[
+			format!("| {:<22} | {:^11} | {:^11} |", "Letters", stats.length, "-"),
+			format!("| {:<22} | {:^11} | {:^11} |", "Distinct letters", stats.distinct_letters, lang_letters),
+			format!("| {:<22} | {:^11} | {:^11} |", "Distinct symbols", stats.distinct_symbols, "-"),
+			format!("| {:<22} | {:^11.4} | {:^11.4} |", "Entropy (bits)", stats.entropy, stats.language_entropy),
+			format!(
+				"| {:<22} | {:^11.4} | {:^11.4} |",
+				"Index of coincidence", stats.coincidence, stats.language_coincidence
+			)
+		].join("\n")
+	}
+
+	fn _format_repeats(stats: &TextStats) -> String {
+		if stats.repeats.is_empty() {
+			return "No sequence of letters repeats in the text".to_owned();
 		}
 
-		alphabet
+		stats
+			.repeats
 			.iter()
-			.map(|(c, _)| c)
-			.collect::<Vec<_>>()
-			.iter()
-			.map(|&c| {
-				format!(
-					"- {}\n",
-					text.replace("?", &*c.to_string())
-				)
+			.map(|r| {
+				let spacings: Vec<_> = r.spacings.iter().map(|s| s.to_string()).collect();
+				format!("[{}] x{}, spaced by: {}", r.sequence, r.count, spacings.join(", "))
 			})
-			.collect()
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	/// Draws the frequency of the letters of the text next to the language's
+	/// ones, rank by rank, as bars scaled to the highest frequency of both.
+	fn _format_histogram(text_freq: &[(char, f64)], lang_freq: &[(char, f64)]) -> String {
+		const WIDTH: f64 = 30.0;
+		let lang_total: f64 = lang_freq.iter().map(|(_, f)| f).sum();
+		let lang_freq: Vec<_> = lang_freq.iter().map(|&(c, f)| (c, f / lang_total)).collect();
+		let max = text_freq.iter().chain(lang_freq.iter()).map(|(_, f)| *f).fold(0.0, f64::max);
+
+		let bar = |entry: Option<&(char, f64)>| match entry {
+			Some(&(c, f)) => format!(
+				"{c} {:<width$} {:>6.2}%",
+				"#".repeat((f / max * WIDTH).round() as usize),
+				f * 100.0,
+				width = WIDTH as usize
+			),
+			None => " ".repeat(WIDTH as usize + 10)
+		};
+		(0..text_freq.len().max(lang_freq.len()))
+			.map(|i| format!("{:>3}. {} | {}", i + 1, bar(text_freq.get(i)), bar(lang_freq.get(i))))
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	fn _format_identification_table(identification: &Identification) -> String {
+		identification
+			.guesses
+			.iter()
+			.enumerate()
+			.map(|(i, g)| format!(
+				"| {:^6} | {:^14} | {:^11.4} | {:^11.4} |",
+				i + 1, g.cipher.to_string(), g.distance, g.confidence
+			))
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	fn _format_detection_table(guesses: &[LanguageGuess]) -> String {
+		guesses
+			.iter()
+			.enumerate()
+			.map(|(i, g)| format!(
+				"| {:^6} | {:^11} | {:^11.4} | {:^11.4} |",
+				i + 1, g.model.name(), g.distance, g.confidence
+			))
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	/// Serializes the report in the format of the logger, or gives nothing
+	/// back when it has to be formatted as tables.
+	fn _serialize<R: Serialize + Rows>(&self, report: &R) -> Option<String> {
+		match self.format {
+			OutputFormat::Table => None,
+			OutputFormat::Json => Some(
+				serde_json::to_string_pretty(report).expect("reports are always serializable") + "\n"
+			),
+			OutputFormat::Csv => Some(
+				std::iter::once(["section".to_owned(), "key".to_owned(), "value".to_owned()])
+					.chain(report.rows())
+					.map(|row| row.iter().map(|f| Self::_csv_field(f)).collect::<Vec<_>>().join(",") + "\n")
+					.collect()
+			)
+		}
+	}
+
+	/// Parses the given report of a nested analysis, so it is embedded into
+	/// the JSON report that wraps it.
+	fn _analysis_value(&self, analysis: Option<&str>) -> Option<serde_json::Value> {
+		match self.format {
+			OutputFormat::Json => analysis.and_then(|a| serde_json::from_str(a).ok()),
+			_ => None
+		}
+	}
+
+	/// Appends the rows of the given report of a nested analysis to the CSV
+	/// report that wraps it, without repeating the header.
+	fn _append_analysis(&self, serialized: String, analysis: Option<&str>) -> String {
+		match (self.format, analysis) {
+			(OutputFormat::Csv, Some(a)) => serialized + a.split_once('\n').map_or("", |(_, rows)| rows),
+			_ => serialized
+		}
+	}
+
+	/// Quotes the field when it holds a delimiter, a quote or a line break.
+	fn _csv_field(field: &str) -> String {
+		match field.contains([',', '"', '\n', '\r']) {
+			true => format!("\"{}\"", field.replace('"', "\"\"")),
+			false => field.to_owned()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::report::SCHEMA_VERSION;
+	use crate::data::model::LanguageModel;
+
+	#[test]
+	fn reports_are_serialized_as_json() {
+		let logger = Logger::with_format(OutputFormat::Json);
+		let candidates = [Candidate { key: "E".to_owned(), text: "EAT".to_owned(), score: 2.5, confidence: 1.0 }];
+		let report = logger.format_report(
+			"English", "QXZ", "EA?", &[('E', 0.5)], &[('Q', 1.0)], &[('Q', 'E')], &candidates
+		);
+		let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+		assert_eq!(json["report"], "frequency");
+		assert_eq!(json["version"], SCHEMA_VERSION);
+		assert_eq!(json["input_frequencies"][0]["character"], "Q");
+		assert_eq!(json["language_frequencies"][0]["frequency"], 1.0);
+		assert_eq!(json["associations"][0]["to"], "E");
+		assert_eq!(json["candidates"][0]["text"], "EAT");
+	}
+
+	#[test]
+	fn reports_are_serialized_as_csv() {
+		let logger = Logger::with_format(OutputFormat::Csv);
+		let report = logger.format_transformation("Hello, \"world\"", "Khoor, \"zruog\"");
+
+		assert_eq!(
+			report,
+			"section,key,value\ntext,,\"Hello, \"\"world\"\"\"\nresult,,\"Khoor, \"\"zruog\"\"\"\n"
+		);
+	}
+
+	#[test]
+	fn detection_wraps_the_analysis() {
+		let model = LanguageModel::parse("name = Tiny\n[letters]\nE 1").unwrap();
+		let guesses = [LanguageGuess {
+			model: &model, divergence: 0.0, coincidence: 1.0, coverage: 1.0, distance: 0.0, confidence: 1.0
+		}];
+
+		let json = Logger::with_format(OutputFormat::Json);
+		let analysis = json.format_transformation("Q", "E");
+		let report: serde_json::Value = serde_json::from_str(&json.format_detection(&guesses, &analysis)).unwrap();
+		assert_eq!(report["languages"][0]["language"], "Tiny");
+		assert_eq!(report["analysis"]["result"], "E");
+
+		let csv = Logger::with_format(OutputFormat::Csv);
+		let analysis = csv.format_transformation("Q", "E");
+		let report = csv.format_detection(&guesses, &analysis);
+		assert_eq!(report.matches("section,key,value").count(), 1);
+		assert!(report.starts_with("section,key,value\ndetection_language,1,Tiny\n"));
+		assert!(report.ends_with("text,,Q\nresult,,E\n"));
 	}
 }
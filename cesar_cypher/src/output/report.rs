@@ -0,0 +1,696 @@
+use serde::Serialize;
+
+use crate::data::{
+	Candidate, CribKey, CribPlacement, HomophonicSolution, Identification, KeywordKey, LanguageGuess, PatternSolution,
+	PolyalphabeticSolution, SubstitutionSolution, TextStats
+};
+
+/// Version of the schema followed by the serialized reports. It is only
+/// increased when a field is removed or changes its meaning.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// A report that can be flattened into `section,key,value` rows.
+///
+/// Every row belongs to a section, which names the piece of data it holds,
+/// while the key tells apart the rows of a same section (a letter, a rank,
+/// etc.) and is left empty for the sections with a single row.
+pub trait Rows {
+	fn rows(&self) -> Vec<[String; 3]>;
+}
+
+/// The frequency of a letter, as its share of all the letters, from 0 to 1.
+#[derive(Debug, Serialize)]
+pub struct CharFrequency {
+	pub character: char,
+	pub frequency: f64
+}
+
+#[derive(Debug, Serialize)]
+pub struct Association {
+	pub from: char,
+	pub to: char
+}
+
+/// The report of the rank-order frequency association of a text.
+#[derive(Debug, Serialize)]
+pub struct FrequencyReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub language: &'a str,
+	pub language_frequencies: Vec<CharFrequency>,
+	pub input_frequencies: Vec<CharFrequency>,
+	pub associations: Vec<Association>,
+	pub text: &'a str,
+	pub result: &'a str,
+	pub candidates: Vec<CandidateEntry<'a>>
+}
+
+/// The report of the statistics of a text.
+#[derive(Debug, Serialize)]
+pub struct StatsReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub language: &'a str,
+	pub length: usize,
+	pub distinct_letters: usize,
+	pub distinct_symbols: usize,
+	pub entropy: f64,
+	pub coincidence: f64,
+	pub language_entropy: f64,
+	pub language_coincidence: f64,
+	pub language_frequencies: Vec<CharFrequency>,
+	pub input_frequencies: Vec<CharFrequency>,
+	pub repeats: Vec<RepeatEntry<'a>>,
+	pub text: &'a str
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepeatEntry<'a> {
+	pub sequence: &'a str,
+	pub count: usize,
+	pub spacings: &'a [usize]
+}
+
+/// The report of the identified cypher of a text.
+#[derive(Debug, Serialize)]
+pub struct IdentificationReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub letter_share: f64,
+	pub encoding: Option<&'a str>,
+	pub frequency_distance: f64,
+	pub profile_distance: f64,
+	pub coincidence: f64,
+	pub period: usize,
+	pub period_coincidence: f64,
+	pub guesses: Vec<CipherEntry>,
+	pub text: &'a str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub analysis: Option<serde_json::Value>
+}
+
+#[derive(Debug, Serialize)]
+pub struct CipherEntry {
+	pub rank: usize,
+	pub cipher: String,
+	pub distance: f64,
+	pub confidence: f64
+}
+
+/// The report of the detected language of a text.
+#[derive(Debug, Serialize)]
+pub struct DetectionReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub languages: Vec<LanguageEntry<'a>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub analysis: Option<serde_json::Value>
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageEntry<'a> {
+	pub rank: usize,
+	pub language: &'a str,
+	pub divergence: f64,
+	pub coincidence: f64,
+	pub coverage: f64,
+	pub distance: f64,
+	pub confidence: f64
+}
+
+/// The report of the placements of a known word of the plain text inside the
+/// cyphered one.
+#[derive(Debug, Serialize)]
+pub struct CribReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub crib: &'a str,
+	pub key: String,
+	pub text: &'a str,
+	pub placements: Vec<PlacementEntry<'a>>
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlacementEntry<'a> {
+	pub rank: usize,
+	pub position: usize,
+	pub mappings: Vec<Association>,
+	pub key: &'a str,
+	pub score: f64,
+	pub confidence: f64,
+	pub text: &'a str
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandidateEntry<'a> {
+	pub rank: usize,
+	pub key: &'a str,
+	pub score: f64,
+	pub confidence: f64,
+	pub text: &'a str
+}
+
+/// The report of the ranked candidates of a brute-force solver.
+#[derive(Debug, Serialize)]
+pub struct CandidatesReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub text: &'a str,
+	pub candidates: Vec<CandidateEntry<'a>>
+}
+
+/// The report of a cracked monoalphabetic substitution.
+#[derive(Debug, Serialize)]
+pub struct SubstitutionReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub key: String,
+	pub fitness: f64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub keyword: Option<KeywordEntry>,
+	pub text: &'a str,
+	pub result: &'a str
+}
+
+/// The keyword and shift a substitution key was generated from.
+#[derive(Debug, Serialize)]
+pub struct KeywordEntry {
+	pub keyword: String,
+	pub shift: usize,
+	pub key: String,
+	pub filled: usize
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeyLengthEntry {
+	pub length: usize,
+	pub coincidence: f64,
+	pub kasiski: f64,
+	pub score: f64
+}
+
+/// The report of a cracked polyalphabetic cypher.
+#[derive(Debug, Serialize)]
+pub struct PolyalphabeticReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub variant: String,
+	pub keyword: &'a str,
+	pub key_lengths: Vec<KeyLengthEntry>,
+	pub text: &'a str,
+	pub result: &'a str
+}
+
+/// The report of a cracked homophonic substitution cypher.
+#[derive(Debug, Serialize)]
+pub struct HomophonicReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub fitness: f64,
+	pub homophones: Vec<HomophoneEntry<'a>>,
+	pub text: &'a str,
+	pub result: &'a str
+}
+
+#[derive(Debug, Serialize)]
+pub struct HomophoneEntry<'a> {
+	pub letter: char,
+	pub symbols: Vec<&'a str>
+}
+
+/// The report of the word pattern search over a text.
+#[derive(Debug, Serialize)]
+pub struct PatternReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub key: String,
+	pub associations: Vec<Association>,
+	pub resolved_words: usize,
+	pub total_words: usize,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub keyword: Option<KeywordEntry>,
+	pub text: &'a str,
+	pub result: &'a str
+}
+
+/// The report of a text cyphered or decyphered with a known key.
+#[derive(Debug, Serialize)]
+pub struct TransformationReport<'a> {
+	pub report: &'static str,
+	pub version: u32,
+	pub text: &'a str,
+	pub result: &'a str
+}
+
+impl<'a> FrequencyReport<'a> {
+	pub fn new(
+		language: &'a str,
+		text: &'a str,
+		result: &'a str,
+		language_freq: &[(char, f64)],
+		text_freq: &[(char, f64)],
+		associations: &[(char, char)],
+		candidates: &'a [Candidate]
+	) -> Self {
+		Self {
+			report: "frequency",
+			version: SCHEMA_VERSION,
+			language,
+			language_frequencies: language_frequencies(language_freq),
+			input_frequencies: char_frequencies(text_freq),
+			associations: association_entries(associations),
+			text,
+			result,
+			candidates: candidate_entries(candidates)
+		}
+	}
+}
+
+impl<'a> StatsReport<'a> {
+	pub fn new(language: &'a str, text: &'a str, stats: &'a TextStats, language_freq: &[(char, f64)]) -> Self {
+		Self {
+			report: "stats",
+			version: SCHEMA_VERSION,
+			language,
+			length: stats.length,
+			distinct_letters: stats.distinct_letters,
+			distinct_symbols: stats.distinct_symbols,
+			entropy: stats.entropy,
+			coincidence: stats.coincidence,
+			language_entropy: stats.language_entropy,
+			language_coincidence: stats.language_coincidence,
+			language_frequencies: language_frequencies(language_freq),
+			input_frequencies: char_frequencies(&stats.frequency),
+			repeats: stats
+				.repeats
+				.iter()
+				.map(|r| RepeatEntry { sequence: &r.sequence, count: r.count, spacings: &r.spacings })
+				.collect(),
+			text
+		}
+	}
+}
+
+impl<'a> IdentificationReport<'a> {
+	pub fn new(text: &'a str, identification: &'a Identification) -> Self {
+		let features = &identification.features;
+		Self {
+			report: "identification",
+			version: SCHEMA_VERSION,
+			letter_share: features.letter_share,
+			encoding: features.encoding,
+			frequency_distance: features.frequency_distance,
+			profile_distance: features.profile_distance,
+			coincidence: features.coincidence,
+			period: features.period,
+			period_coincidence: features.period_coincidence,
+			guesses: identification
+				.guesses
+				.iter()
+				.enumerate()
+				.map(|(i, g)| CipherEntry {
+					rank: i + 1,
+					cipher: g.cipher.to_string(),
+					distance: g.distance,
+					confidence: g.confidence
+				})
+				.collect(),
+			text,
+			analysis: None
+		}
+	}
+}
+
+impl<'a> DetectionReport<'a> {
+	pub fn new(guesses: &[LanguageGuess<'a>]) -> Self {
+		Self {
+			report: "detection",
+			version: SCHEMA_VERSION,
+			languages: guesses
+				.iter()
+				.enumerate()
+				.map(|(i, g)| LanguageEntry {
+					rank: i + 1,
+					language: g.model.name(),
+					divergence: g.divergence,
+					coincidence: g.coincidence,
+					coverage: g.coverage,
+					distance: g.distance,
+					confidence: g.confidence
+				})
+				.collect(),
+			analysis: None
+		}
+	}
+}
+
+impl<'a> CribReport<'a> {
+	pub fn new(text: &'a str, crib: &'a str, key: CribKey, placements: &'a [CribPlacement]) -> Self {
+		Self {
+			report: "crib",
+			version: SCHEMA_VERSION,
+			crib,
+			key: key.to_string(),
+			text,
+			placements: placements
+				.iter()
+				.enumerate()
+				.map(|(i, p)| PlacementEntry {
+					rank: i + 1,
+					position: p.position,
+					mappings: association_entries(&p.mappings.associations()),
+					key: &p.candidate.key,
+					score: p.candidate.score,
+					confidence: p.candidate.confidence,
+					text: &p.candidate.text
+				})
+				.collect()
+		}
+	}
+}
+
+impl<'a> CandidatesReport<'a> {
+	pub fn new(text: &'a str, candidates: &'a [Candidate]) -> Self {
+		Self {
+			report: "candidates",
+			version: SCHEMA_VERSION,
+			text,
+			candidates: candidate_entries(candidates)
+		}
+	}
+}
+
+impl<'a> SubstitutionReport<'a> {
+	pub fn new(text: &'a str, solution: &'a SubstitutionSolution, keyword: Option<&KeywordKey>) -> Self {
+		Self {
+			report: "substitution",
+			version: SCHEMA_VERSION,
+			key: solution.key.to_string(),
+			fitness: solution.score,
+			keyword: keyword.map(KeywordEntry::new),
+			text,
+			result: &solution.text
+		}
+	}
+}
+
+impl<'a> PolyalphabeticReport<'a> {
+	/// Builds the report, keeping only the given amount of the most likely
+	/// key lengths.
+	pub fn new(text: &'a str, solution: &'a PolyalphabeticSolution, top: usize) -> Self {
+		Self {
+			report: "polyalphabetic",
+			version: SCHEMA_VERSION,
+			variant: format!("{:?}", solution.variant).to_lowercase(),
+			keyword: &solution.keyword,
+			key_lengths: solution.key_lengths
+				.iter()
+				.take(top)
+				.map(|k| KeyLengthEntry {
+					length: k.length,
+					coincidence: k.coincidence,
+					kasiski: k.kasiski,
+					score: k.score
+				})
+				.collect(),
+			text,
+			result: &solution.text
+		}
+	}
+}
+
+impl<'a> HomophonicReport<'a> {
+	pub fn new(text: &'a str, solution: &'a HomophonicSolution) -> Self {
+		Self {
+			report: "homophonic",
+			version: SCHEMA_VERSION,
+			fitness: solution.score,
+			homophones: solution
+				.homophones()
+				.into_iter()
+				.map(|(letter, symbols)| HomophoneEntry { letter, symbols })
+				.collect(),
+			text,
+			result: &solution.text
+		}
+	}
+}
+
+impl<'a> PatternReport<'a> {
+	pub fn new(text: &'a str, result: &'a str, solution: &PatternSolution, keyword: Option<&KeywordKey>) -> Self {
+		Self {
+			report: "pattern",
+			version: SCHEMA_VERSION,
+			key: solution.key.to_string(),
+			associations: association_entries(&solution.key.text_associations(text)),
+			resolved_words: solution.matched_words,
+			total_words: solution.total_words,
+			keyword: keyword.map(KeywordEntry::new),
+			text,
+			result
+		}
+	}
+}
+
+impl KeywordEntry {
+	pub fn new(keyword: &KeywordKey) -> Self {
+		Self {
+			keyword: keyword.keyword.clone(),
+			shift: keyword.shift,
+			key: keyword.key.to_string(),
+			filled: keyword.filled
+		}
+	}
+
+	fn rows(&self) -> Vec<[String; 3]> {
+		vec![
+			row("keyword", "", &self.keyword),
+			row("keyword_shift", "", self.shift),
+			row("keyword_key", "", &self.key),
+			row("keyword_filled", "", self.filled)
+		]
+	}
+}
+
+impl<'a> TransformationReport<'a> {
+	pub fn new(text: &'a str, result: &'a str) -> Self {
+		Self { report: "transformation", version: SCHEMA_VERSION, text, result }
+	}
+}
+
+fn candidate_entries(candidates: &[Candidate]) -> Vec<CandidateEntry<'_>> {
+	candidates
+		.iter()
+		.enumerate()
+		.map(|(i, c)| CandidateEntry {
+			rank: i + 1,
+			key: &c.key,
+			score: c.score,
+			confidence: c.confidence,
+			text: &c.text
+		})
+		.collect()
+}
+
+fn char_frequencies(freq: &[(char, f64)]) -> Vec<CharFrequency> {
+	freq
+		.iter()
+		.map(|&(character, frequency)| CharFrequency { character, frequency })
+		.collect()
+}
+
+/// Builds the frequencies of the language, which the models give as
+/// percentages, as shares of all the letters like the ones of the text.
+fn language_frequencies(freq: &[(char, f64)]) -> Vec<CharFrequency> {
+	let total: f64 = freq.iter().map(|(_, f)| f).sum();
+	freq
+		.iter()
+		.map(|&(character, f)| CharFrequency { character, frequency: if total > 0.0 { f / total } else { 0.0 } })
+		.collect()
+}
+
+fn association_entries(ass: &[(char, char)]) -> Vec<Association> {
+	ass
+		.iter()
+		.map(|&(from, to)| Association { from, to })
+		.collect()
+}
+
+/// Builds a row out of its three columns.
+fn row<K: ToString, V: ToString>(section: &str, key: K, value: V) -> [String; 3] {
+	[section.to_string(), key.to_string(), value.to_string()]
+}
+
+/// Builds the rows of the given ranked candidates.
+fn candidate_rows(candidates: &[CandidateEntry]) -> Vec<[String; 3]> {
+	candidates
+		.iter()
+		.flat_map(|c| [
+			row("candidate_key", c.rank, c.key),
+			row("candidate_score", c.rank, c.score),
+			row("candidate_confidence", c.rank, c.confidence),
+			row("candidate_text", c.rank, c.text)
+		])
+		.collect()
+}
+
+impl Rows for FrequencyReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![row("language", "", self.language)];
+		rows.extend(self.language_frequencies.iter().map(|f| row("language_frequency", f.character, f.frequency)));
+		rows.extend(self.input_frequencies.iter().map(|f| row("input_frequency", f.character, f.frequency)));
+		rows.extend(self.associations.iter().map(|a| row("association", a.from, a.to)));
+		rows.push(row("text", "", self.text));
+		rows.push(row("result", "", self.result));
+		rows.extend(candidate_rows(&self.candidates));
+
+		rows
+	}
+}
+
+impl Rows for StatsReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![
+			row("language", "", self.language),
+			row("length", "", self.length),
+			row("distinct_letters", "", self.distinct_letters),
+			row("distinct_symbols", "", self.distinct_symbols),
+			row("entropy", "", self.entropy),
+			row("coincidence", "", self.coincidence),
+			row("language_entropy", "", self.language_entropy),
+			row("language_coincidence", "", self.language_coincidence)
+		];
+		rows.extend(self.language_frequencies.iter().map(|f| row("language_frequency", f.character, f.frequency)));
+		rows.extend(self.input_frequencies.iter().map(|f| row("input_frequency", f.character, f.frequency)));
+		for r in self.repeats.iter() {
+			let spacings: Vec<_> = r.spacings.iter().map(|s| s.to_string()).collect();
+			rows.push(row("repeat_count", r.sequence, r.count));
+			rows.push(row("repeat_spacings", r.sequence, spacings.join(" ")));
+		}
+		rows.push(row("text", "", self.text));
+
+		rows
+	}
+}
+
+impl Rows for IdentificationReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![
+			row("letter_share", "", self.letter_share),
+			row("encoding", "", self.encoding.unwrap_or_default()),
+			row("frequency_distance", "", self.frequency_distance),
+			row("profile_distance", "", self.profile_distance),
+			row("coincidence", "", self.coincidence),
+			row("period", "", self.period),
+			row("period_coincidence", "", self.period_coincidence)
+		];
+		for g in self.guesses.iter() {
+			rows.push(row("cipher", g.rank, &g.cipher));
+			rows.push(row("cipher_distance", g.rank, g.distance));
+			rows.push(row("cipher_confidence", g.rank, g.confidence));
+		}
+		rows.push(row("text", "", self.text));
+
+		rows
+	}
+}
+
+impl Rows for DetectionReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = Vec::new();
+		for l in self.languages.iter() {
+			rows.push(row("detection_language", l.rank, l.language));
+			rows.push(row("detection_divergence", l.rank, l.divergence));
+			rows.push(row("detection_coincidence", l.rank, l.coincidence));
+			rows.push(row("detection_coverage", l.rank, l.coverage));
+			rows.push(row("detection_distance", l.rank, l.distance));
+			rows.push(row("detection_confidence", l.rank, l.confidence));
+		}
+
+		rows
+	}
+}
+
+impl Rows for CandidatesReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![row("text", "", self.text)];
+		rows.extend(candidate_rows(&self.candidates));
+
+		rows
+	}
+}
+
+impl Rows for CribReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![row("crib", "", self.crib), row("key", "", &self.key), row("text", "", self.text)];
+		for p in self.placements.iter() {
+			rows.push(row("placement_position", p.rank, p.position));
+			rows.extend(p.mappings.iter().map(|a| row("placement_mapping", p.rank, format!("{}={}", a.from, a.to))));
+			rows.extend([
+				row("placement_key", p.rank, p.key),
+				row("placement_score", p.rank, p.score),
+				row("placement_confidence", p.rank, p.confidence),
+				row("placement_text", p.rank, p.text)
+			]);
+		}
+
+		rows
+	}
+}
+
+impl Rows for SubstitutionReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![row("key", "", &self.key), row("fitness", "", self.fitness)];
+		rows.extend(self.keyword.iter().flat_map(KeywordEntry::rows));
+		rows.push(row("text", "", self.text));
+		rows.push(row("result", "", self.result));
+
+		rows
+	}
+}
+
+impl Rows for PolyalphabeticReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![row("variant", "", &self.variant), row("keyword", "", self.keyword)];
+		for k in self.key_lengths.iter() {
+			rows.push(row("key_length_coincidence", k.length, k.coincidence));
+			rows.push(row("key_length_kasiski", k.length, k.kasiski));
+			rows.push(row("key_length_score", k.length, k.score));
+		}
+		rows.push(row("text", "", self.text));
+		rows.push(row("result", "", self.result));
+
+		rows
+	}
+}
+
+impl Rows for HomophonicReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![row("fitness", "", self.fitness)];
+		rows.extend(self.homophones.iter().map(|h| row("homophones", h.letter, h.symbols.join(" "))));
+		rows.push(row("text", "", self.text));
+		rows.push(row("result", "", self.result));
+
+		rows
+	}
+}
+
+impl Rows for PatternReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		let mut rows = vec![row("key", "", &self.key)];
+		rows.extend(self.associations.iter().map(|a| row("association", a.from, a.to)));
+		rows.push(row("resolved_words", "", self.resolved_words));
+		rows.push(row("total_words", "", self.total_words));
+		rows.extend(self.keyword.iter().flat_map(KeywordEntry::rows));
+		rows.push(row("text", "", self.text));
+		rows.push(row("result", "", self.result));
+
+		rows
+	}
+}
+
+impl Rows for TransformationReport<'_> {
+	fn rows(&self) -> Vec<[String; 3]> {
+		vec![row("text", "", self.text), row("result", "", self.result)]
+	}
+}
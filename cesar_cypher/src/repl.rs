@@ -0,0 +1,414 @@
+use std::{fs, io::{BufRead, Write}, path::PathBuf, str::FromStr};
+
+use crate::{data::{fold_case, Analizer, SubstitutionKey}, error::Error};
+
+/// Width, in characters, of the lines shown by the side by side view.
+const VIEW_WIDTH: usize = 64;
+
+const HELP: &str = "\
+Commands:
+  set Q=E      map the cyphered letter Q to the plain letter E (or `?`)
+  swap Q W     exchange the plain letters of the cyphered letters Q and W
+  lock Q       keep the mapping of Q from being changed
+  unlock Q     allow the mapping of Q to be changed again
+  undo, redo   revert or reapply the last change
+  show         show the cyphered and the plain texts side by side
+  freq         show the frequency table of the current key
+  key          show the current key
+  export FILE  write the current key to the given file, as its plain alphabet
+  reset        go back to the key found by the frequency analysis
+  help         show this message
+  quit         finish the session and print the report
+";
+
+/// A command typed during an interactive session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+	Set(char, char),
+	Swap(char, char),
+	Lock(char),
+	Unlock(char),
+	Undo,
+	Redo,
+	Show,
+	Freq,
+	Key,
+	Export(PathBuf),
+	Reset,
+	Help,
+	Quit
+}
+
+impl FromStr for Command {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let letter = |arg: &str| -> Result<char, String> {
+			let mut chars = arg.trim().chars();
+			match (chars.next(), chars.next()) {
+				(Some(c), None) if c.is_alphabetic() || c == '?' => Ok(fold_case(c)),
+				_ => Err(format!(r#"invalid letter "{}""#, arg.trim()))
+			}
+		};
+
+		let (name, rest) = s.trim().split_once(char::is_whitespace).unwrap_or((s.trim(), ""));
+		let rest = rest.trim();
+		match name.to_lowercase().as_str() {
+			"set" => {
+				let (c, p) = rest
+					.split_once('=')
+					.ok_or_else(|| format!(r#"invalid mapping "{rest}". expected Q=E"#))?;
+				Ok(Self::Set(letter(c)?, letter(p)?))
+			},
+			"swap" => match rest.split_whitespace().collect::<Vec<_>>().as_slice() {
+				[a, b] => Ok(Self::Swap(letter(a)?, letter(b)?)),
+				_ => Err("swap expects two letters".to_owned())
+			},
+			"lock" => Ok(Self::Lock(letter(rest)?)),
+			"unlock" => Ok(Self::Unlock(letter(rest)?)),
+			"undo" => Ok(Self::Undo),
+			"redo" => Ok(Self::Redo),
+			"show" => Ok(Self::Show),
+			"freq" => Ok(Self::Freq),
+			"key" => Ok(Self::Key),
+			"export" if !rest.is_empty() => Ok(Self::Export(PathBuf::from(rest))),
+			"export" => Err("export expects a file".to_owned()),
+			"reset" => Ok(Self::Reset),
+			"help" => Ok(Self::Help),
+			"quit" | "exit" => Ok(Self::Quit),
+			unk => Err(format!(r#"unknown command "{unk}". type `help` to list them"#))
+		}
+	}
+}
+
+/// The key being refined, along with the mappings locked by the user.
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+	key: SubstitutionKey,
+	locked: Vec<char>
+}
+
+/// This struct represents an interactive session in which the key found by
+/// the frequency analysis is refined by hand.
+///
+/// Every change to the key is recorded, so it can be undone and redone.
+pub struct Session<'a> {
+	analizer: &'a Analizer,
+	text: &'a str,
+	text_freq: Vec<(char, f64)>,
+	seed: State,
+	state: State,
+	undone: Vec<State>,
+	history: Vec<State>
+}
+
+impl<'a> Session<'a> {
+	/// Creates a new session over the given text, seeded with the key given
+	/// by the rank-order association of its frequency with the language's one.
+	pub fn new(analizer: &'a Analizer, text: &'a str) -> Result<Self, Error> {
+		let text_freq = analizer.stream_frequency(text.as_bytes(), false)?;
+		let seed = State {
+			key: analizer.frequency_key(text, &text_freq)?,
+			locked: Vec::new()
+		};
+
+		Ok(Self {
+			analizer,
+			text,
+			text_freq,
+			state: seed.clone(),
+			seed,
+			undone: Vec::new(),
+			history: Vec::new()
+		})
+	}
+
+	/// Retrieves the current key.
+	pub fn key(&self) -> &SubstitutionKey {
+		&self.state.key
+	}
+
+	/// Retrieves the frequency of the letters of the text.
+	pub fn text_freq(&self) -> &[(char, f64)] {
+		&self.text_freq
+	}
+
+	/// Reads commands from the input until it is exhausted or the session is
+	/// quitted, writing their outcome to the output.
+	pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> Result<(), Error> {
+		writeln!(output, "{}", self.view().unwrap_or_else(|err| format!("error: {err}")))?;
+		writeln!(output, "Type `help` to list the available commands.")?;
+
+		let mut lines = input.lines();
+		loop {
+			write!(output, "> ")?;
+			output.flush()?;
+
+			let line = match lines.next() {
+				Some(line) => line?,
+				None => break
+			};
+			if line.trim().is_empty() { continue; }
+
+			let outcome = line
+				.parse::<Command>()
+				.and_then(|cmd| match cmd {
+					Command::Quit => Ok(None),
+					cmd => self.execute(cmd).map(Some)
+				});
+			match outcome {
+				Ok(Some(message)) => writeln!(output, "{message}")?,
+				Ok(None) => break,
+				Err(err) => writeln!(output, "error: {err}")?
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Executes the given command, giving back the message to show.
+	fn execute(&mut self, cmd: Command) -> Result<String, String> {
+		match cmd {
+			Command::Set(c, p) => {
+				self.plain_of(c)?;
+				self.ensure_unlocked(c)?;
+				let plain = match p {
+					'?' => None,
+					p => Some(index(p)?)
+				};
+
+				// the letter that was already mapped to the plain one takes the
+				// former plain letter of the cyphered one, so the key stays
+				// bijective
+				let holder = plain.and_then(|p| self.state.key.inverse().get(p)).map(|o| (o + b'A') as char);
+				if let Some(o) = holder.filter(|&o| o != c) {
+					self.ensure_unlocked(o)?;
+				}
+
+				let mut state = self.state.clone();
+				state.key.set(index(c)?, plain);
+
+				self.commit(state);
+				self.view()
+			},
+			Command::Swap(a, b) => {
+				self.plain_of(a)?;
+				self.plain_of(b)?;
+				self.ensure_unlocked(a)?;
+				self.ensure_unlocked(b)?;
+
+				let mut state = self.state.clone();
+				state.key.swap(index(a)? as usize, index(b)? as usize);
+
+				self.commit(state);
+				self.view()
+			},
+			Command::Lock(c) => {
+				self.plain_of(c)?;
+				let mut state = self.state.clone();
+				if !state.locked.contains(&c) { state.locked.push(c); }
+
+				self.commit(state);
+				Ok(format!("{c} is locked"))
+			},
+			Command::Unlock(c) => {
+				let mut state = self.state.clone();
+				state.locked.retain(|&l| l != c);
+
+				self.commit(state);
+				Ok(format!("{c} is unlocked"))
+			},
+			Command::Undo => {
+				let prev = self.history.pop().ok_or("there is nothing to undo")?;
+				self.undone.push(std::mem::replace(&mut self.state, prev));
+				self.view()
+			},
+			Command::Redo => {
+				let next = self.undone.pop().ok_or("there is nothing to redo")?;
+				self.history.push(std::mem::replace(&mut self.state, next));
+				self.view()
+			},
+			Command::Show => self.view(),
+			Command::Freq => Ok(self.freq_table()),
+			Command::Key => Ok(self.key_table()),
+			Command::Export(path) => {
+				fs::write(&path, format!("{}\n", self.state.key)).map_err(|err| err.to_string())?;
+				Ok(format!("key written to {}", path.display()))
+			},
+			Command::Reset => {
+				self.commit(self.seed.clone());
+				self.view()
+			},
+			Command::Help => Ok(HELP.to_owned()),
+			Command::Quit => Ok(String::new())
+		}
+	}
+
+	/// Replaces the current state, recording the previous one.
+	fn commit(&mut self, state: State) {
+		self.history.push(std::mem::replace(&mut self.state, state));
+		self.undone.clear();
+	}
+
+	/// Retrieves the plain letter the given cyphered one is mapped to, or `?`
+	/// when it is unknown.
+	fn plain_of(&self, c: char) -> Result<char, String> {
+		if !self.text_freq.iter().any(|(o, _)| *o == c) {
+			return Err(format!("{c} does not appear in the text"));
+		}
+
+		Ok(self.state.key.get(index(c)?).map_or('?', |p| (p + b'A') as char))
+	}
+
+	fn ensure_unlocked(&self, c: char) -> Result<(), String> {
+		match self.state.locked.contains(&c) {
+			true => Err(format!("{c} is locked")),
+			false => Ok(())
+		}
+	}
+
+	/// Shows the cyphered text and the current plain text, a line above the
+	/// other.
+	fn view(&self) -> Result<String, String> {
+		let plain = self.analizer
+			.assemble_result(self.text, &self.state.key.text_associations(self.text))
+			.map_err(|err| err.to_string())?;
+		let cyphered: Vec<char> = self.text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+		let plain: Vec<char> = plain.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+
+		Ok(cyphered
+			.chunks(VIEW_WIDTH)
+			.zip(plain.chunks(VIEW_WIDTH))
+			.map(|(c, p)| {
+				format!(
+					"[C]: {}\n[P]: {}\n",
+					c.iter().collect::<String>(),
+					p.iter().collect::<String>()
+				)
+			})
+			.collect())
+	}
+
+	/// Shows the frequency of every cyphered letter next to the one of the
+	/// plain letter it is mapped to.
+	fn freq_table(&self) -> String {
+		let lang_freq = self.analizer.get_language_freqs();
+		let rows = self.text_freq
+			.iter()
+			.map(|&(c, f)| {
+				let p = self.plain_of(c).unwrap_or('?');
+				let lang = lang_freq
+					.iter()
+					.find(|(l, _)| *l == p)
+					.map_or("-".to_owned(), |(_, f)| format!("{f:.4}"));
+				let lock = if self.state.locked.contains(&c) { "*" } else { "" };
+				format!("| {c:^11} | {:^11.4} | {:^11} | {lang:^11} | {lock:^6} |", f * 100.0, p)
+			})
+			.collect::<Vec<String>>()
+			.join("\n");
+
+		format!("\
++-------------+-------------+-------------+-------------+--------+
+|  Cyphered   |  Frequency  |    Plain    |  Frequency  | Locked |
++-------------+-------------+-------------+-------------+--------+
+{rows}
++-------------+-------------+-------------+-------------+--------+")
+	}
+
+	/// Shows the current key as the plain letter of every cyphered letter of
+	/// the alphabet, with `-` for the unknown ones.
+	fn key_table(&self) -> String {
+		let cyphered: String = (b'A'..=b'Z').map(char::from).collect();
+
+		format!("[Cyphered]: {cyphered}\n[Plain]:    {}", self.state.key)
+	}
+}
+
+/// Retrieves the position inside the alphabet of the given letter, which the
+/// keys are limited to.
+fn index(c: char) -> Result<u8, String> {
+	match c {
+		'A'..='Z' => Ok(c as u8 - b'A'),
+		_ => Err(format!("{c} is not a letter of the latin alphabet"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::data::model::LanguageModel;
+
+	fn plain_of(session: &Session, c: char) -> char {
+		session.plain_of(c).unwrap()
+	}
+
+	#[test]
+	fn commands_are_parsed() {
+		assert_eq!("set q=e".parse(), Ok(Command::Set('Q', 'E')));
+		assert_eq!("swap A B".parse(), Ok(Command::Swap('A', 'B')));
+		assert!("set q".parse::<Command>().is_err());
+		assert!("fly".parse::<Command>().is_err());
+	}
+
+	#[test]
+	fn mappings_are_set_and_swapped() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let mut session = Session::new(&analizer, "QQQQXXXZZM").unwrap();
+
+		// Q is mapped to E and X to A by the frequency analysis
+		session.execute(Command::Set('X', 'E')).unwrap();
+		assert_eq!((plain_of(&session, 'X'), plain_of(&session, 'Q')), ('E', 'A'));
+
+		session.execute(Command::Swap('X', 'Q')).unwrap();
+		assert_eq!((plain_of(&session, 'X'), plain_of(&session, 'Q')), ('A', 'E'));
+
+		session.execute(Command::Lock('Q')).unwrap();
+		assert!(session.execute(Command::Set('Q', 'T')).is_err());
+		assert!(session.execute(Command::Set('Z', 'E')).is_err());
+		assert!(session.execute(Command::Set('W', 'E')).is_err());
+	}
+
+	#[test]
+	fn changes_are_undone_and_redone() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let mut session = Session::new(&analizer, "QQQQXXXZZM").unwrap();
+		let seed = session.key().clone();
+
+		session.execute(Command::Set('M', 'T')).unwrap();
+		let changed = session.key().clone();
+		session.execute(Command::Undo).unwrap();
+		assert_eq!(session.key(), &seed);
+		session.execute(Command::Redo).unwrap();
+		assert_eq!(session.key(), &changed);
+		assert!(session.execute(Command::Redo).is_err());
+	}
+
+	#[test]
+	fn sessions_are_run() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let mut session = Session::new(&analizer, "Uif dbu").unwrap();
+		let mut output = Vec::new();
+
+		session
+			.run("set U=T\nset I=H\nset F=E\nset D=C\nset B=A\nquit\nset F=X\n".as_bytes(), &mut output)
+			.unwrap();
+
+		let output = String::from_utf8(output).unwrap();
+		assert!(output.contains("[P]: The cat"));
+		assert_eq!(session.key().apply("Uif dbu"), "The cat");
+	}
+
+	#[test]
+	fn exported_keys_are_parsed_back() {
+		let analizer = Analizer::new(LanguageModel::default());
+		let mut session = Session::new(&analizer, "Uif dbu").unwrap();
+		let path = std::env::temp_dir().join(format!("cesar_cypher_export_{}.key", std::process::id()));
+
+		session.execute(Command::Set('U', 'T')).unwrap();
+		session.execute(Command::Export(path.clone())).unwrap();
+		let exported = fs::read_to_string(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(exported.parse::<SubstitutionKey>().unwrap(), *session.key());
+	}
+}